@@ -12,6 +12,7 @@ use crate::git::GitRefField;
 #[derive(Serialize, Deserialize)]
 pub struct Branches {}
 
+#[allow(clippy::derivable_impls)]
 impl Default for Branches {
     fn default() -> Self {
         Self {}
@@ -117,6 +118,7 @@ impl Branches {
     }
 }
 
+#[allow(clippy::derive_ord_xor_partial_ord)]
 #[derive(Debug, Eq, Ord)]
 struct ForEachRef<'a> {
     author_name: &'a str,
@@ -129,6 +131,7 @@ struct ForEachRef<'a> {
     diverged: bool,
 }
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl<'a> PartialOrd for ForEachRef<'a> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match self.is_local.partial_cmp(&other.is_local) {
@@ -196,6 +199,7 @@ impl<'a> ForEachRef<'a> {
         }))
     }
 
+    #[allow(clippy::wrong_self_convention)]
     fn to_row(self, include_remotes: bool) -> Option<Row> {
         if self.diverged {
             assert!(self.is_local);
@@ -228,6 +232,7 @@ impl<'a> ForEachRef<'a> {
     }
 }
 
+#[allow(clippy::into_iter_on_ref)]
 fn split_authors(authors: &[String]) -> Vec<Vec<String>> {
     authors
         .into_iter()
@@ -0,0 +1,188 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Programs used to read and write the clipboard.
+/// Found on `PATH` when not set: `wl-paste`/`wl-copy` on Wayland, then `xclip` and `xsel`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Clipboard {
+    /// Prints the clipboard, e.g. `["wl-paste", "--no-newline"]`
+    paste: Option<Vec<String>>,
+    /// Reads the text to copy from stdin, e.g. `["wl-copy"]`
+    copy: Option<Vec<String>>,
+}
+
+const PASTE: [&[&str]; 3] = [
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-out"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+const COPY: [&[&str]; 3] = [
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard", "-in"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+impl Clipboard {
+    /// The text on the clipboard.
+    pub fn paste(&self) -> Result<String> {
+        let command = match &self.paste {
+            Some(command) => command.clone(),
+            None => discover(&PASTE).ok_or_else(|| {
+                anyhow!(
+                    "No clipboard program found, install wl-paste, xclip or xsel or set `clipboard.paste`"
+                )
+            })?,
+        };
+
+        let (program, args) = split(&command)?;
+        let output = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| anyhow!("Could not run `{program}`: {e}"))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("`{program}` failed with {}", output.status));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Put `text` on the clipboard.
+    /// Falls back to the OSC 52 escape sequence, which many terminals
+    /// support even over SSH, when no program is found.
+    pub fn copy(&self, text: &str) -> Result<()> {
+        let command = match &self.copy {
+            Some(command) => command.clone(),
+            None => match discover(&COPY) {
+                Some(command) => command,
+                None => return osc52(text),
+            },
+        };
+
+        let (program, args) = split(&command)?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("Could not run `{program}`: {e}"))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(anyhow!("`{program}` failed with {status}"));
+        }
+
+        Ok(())
+    }
+}
+
+fn split(command: &[String]) -> Result<(&String, &[String])> {
+    command
+        .split_first()
+        .ok_or_else(|| anyhow!("The clipboard command is empty"))
+}
+
+/// The first of `commands` whose program is on `PATH`.
+/// The Wayland programs are only used in a Wayland session.
+fn discover(commands: &[&[&str]]) -> Option<Vec<String>> {
+    let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+
+    commands
+        .iter()
+        .filter(|command| wayland || !command[0].starts_with("wl-"))
+        .find(|command| on_path(command[0]))
+        .map(|command| command.iter().map(|s| s.to_string()).collect())
+}
+
+fn on_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// Ask the terminal to set the clipboard.
+fn osc52(text: &str) -> Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+
+    if let Ok(mut tty) = OpenOptions::new().write(true).open("/dev/tty") {
+        tty.write_all(sequence.as_bytes())?;
+        return Ok(());
+    }
+
+    let mut stderr = io::stderr();
+    if !stderr.is_terminal() {
+        return Err(anyhow!(
+            "No clipboard program found, install wl-copy, xclip or xsel or set `clipboard.copy`"
+        ));
+    }
+
+    stderr.write_all(sequence.as_bytes())?;
+    Ok(())
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"https://a.b/?c"), "aHR0cHM6Ly9hLmIvP2M=");
+    }
+
+    #[test]
+    fn test_stub_commands() {
+        let file = env::temp_dir().join(format!("bits-clipboard-{}", std::process::id()));
+        let clipboard = Clipboard {
+            paste: Some(vec!["printf".into(), "TEST-1\\n#2".into()]),
+            copy: Some(vec![
+                "sh".into(),
+                "-c".into(),
+                format!("cat > {}", file.display()),
+            ]),
+        };
+
+        assert_eq!(clipboard.paste().unwrap(), "TEST-1\n#2");
+
+        clipboard.copy("https://example.com").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "https://example.com");
+        fs::remove_file(file).unwrap();
+    }
+}
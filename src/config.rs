@@ -1,6 +1,12 @@
 use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
+use regex::{Captures, Match, Regex};
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::ops::Range;
 use std::path::PathBuf;
+use toml::Spanned;
 
 use crate::{branches::Branches, open::Open};
 
@@ -17,13 +23,21 @@ impl Config {
         let config_file = Self::get_config_file(config_file)?;
         let contents = std::fs::read_to_string(&config_file)?;
 
-        match toml::from_str(&contents) {
-            Ok(c) => Ok(c),
+        let file = config_file.to_str().unwrap_or("config");
+        let mut config: Self = match toml::from_str(&contents) {
+            Ok(c) => c,
             Err(e) => {
-                let file = config_file.to_str().unwrap_or("config");
-                Err(anyhow!("{file} :: {}", e.message()))
+                return Err(anyhow!("{file} :: {}", e.message()));
             }
-        }
+        };
+
+        let mut errors = ConfigErrors::new(&contents);
+        config.open.apply_presets(&mut errors);
+        config.open.apply_match_modes();
+        config.open.validate(&mut errors);
+        errors.into_result(file)?;
+
+        Ok(config)
     }
 
     fn get_config_file(config_file: Option<PathBuf>) -> Result<PathBuf> where {
@@ -43,3 +57,255 @@ impl Config {
         ))
     }
 }
+
+/// Problems found in a parsed config, reported together.
+pub struct ConfigErrors<'a> {
+    contents: &'a str,
+    errors: Vec<(Option<usize>, String)>,
+}
+
+impl<'a> ConfigErrors<'a> {
+    pub fn new(contents: &'a str) -> Self {
+        Self {
+            contents,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Record an error for the value at table `path`, located at `span` in the file.
+    pub fn push(&mut self, path: &str, span: Range<usize>, message: impl Display) {
+        let start = span.start.min(self.contents.len());
+        let line = self.contents[..start].matches('\n').count() + 1;
+        self.errors.push((Some(line), format!("{path}: {message}")));
+    }
+
+    /// Record an error for a value that has no location, e.g. a missing one.
+    pub fn push_missing(&mut self, path: &str, message: impl Display) {
+        self.errors.push((None, format!("{path}: {message}")));
+    }
+
+    pub fn into_result(mut self, file: &str) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+
+        self.errors.sort();
+        let errors = self
+            .errors
+            .iter()
+            .map(|(line, error)| match line {
+                Some(line) => format!("{file}:{line}: {error}"),
+                None => format!("{file}: {error}"),
+            })
+            .collect::<Vec<_>>();
+
+        Err(anyhow!(
+            "Invalid config, {} error(s):\n{}",
+            errors.len(),
+            errors.join("\n")
+        ))
+    }
+}
+
+/// A regex from the config, compiled once when the config is loaded.
+#[derive(Debug, Clone)]
+pub struct ConfigRegex {
+    source: Spanned<String>,
+    regex: Result<Regex, regex::Error>,
+}
+
+impl ConfigRegex {
+    pub fn new(source: &str) -> Self {
+        Self::from(Spanned::new(0..0, source.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.source.get_ref()
+    }
+
+    /// Always `None` for a regex that failed to compile,
+    /// those are reported by [`ConfigRegex::validate`].
+    pub fn captures<'t>(&self, text: &'t str) -> Option<Captures<'t>> {
+        self.regex.as_ref().ok()?.captures(text)
+    }
+
+    /// The same regex, anchored at the start and/or the end of the text.
+    pub fn anchored(&self, start: bool, end: bool) -> Self {
+        let regex = format!(
+            "{}(?:{}){}",
+            if start { "^" } else { "" },
+            self.as_str(),
+            if end { "$" } else { "" }
+        );
+
+        Self {
+            source: self.source.clone(),
+            regex: Regex::new(&regex),
+        }
+    }
+
+    /// The first match in `text`.
+    pub fn find<'t>(&self, text: &'t str) -> Option<Match<'t>> {
+        self.regex.as_ref().ok()?.find(text)
+    }
+
+    /// Every non-overlapping match in `text`.
+    pub fn find_iter<'t>(&self, text: &'t str) -> Vec<Match<'t>> {
+        match &self.regex {
+            Ok(regex) => regex.find_iter(text).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// The names of all groups, `None` for unnamed ones.
+    /// The first entry is the implicit group for the whole match.
+    pub fn capture_names(&self) -> impl Iterator<Item = Option<&str>> {
+        self.regex
+            .as_ref()
+            .into_iter()
+            .flat_map(|regex| regex.capture_names())
+    }
+
+    pub fn validate(&self, path: &str, errors: &mut ConfigErrors) {
+        if let Err(e) = &self.regex {
+            errors.push(path, self.source.span(), e);
+        }
+    }
+}
+
+impl PartialEq for ConfigRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ConfigRegex {}
+
+impl From<Spanned<String>> for ConfigRegex {
+    fn from(source: Spanned<String>) -> Self {
+        let regex = Regex::new(source.get_ref());
+        Self { source, regex }
+    }
+}
+
+impl Display for ConfigRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Spanned::<String>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl Serialize for ConfigRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_str().serialize(serializer)
+    }
+}
+
+/// (De)serialize a table as its entries, in the order they are written.
+pub mod ordered {
+    use super::*;
+
+    pub fn deserialize<'de, D, V>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        struct Entries<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for Entries<V> {
+            type Value = Vec<(String, V)>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a table")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(entries)
+            }
+        }
+
+        deserializer.deserialize_map(Entries(PhantomData))
+    }
+
+    pub fn serialize<S, V>(entries: &[(String, V)], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize,
+    {
+        serializer.collect_map(entries.iter().map(|(k, v)| (k, v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Patterns {
+        patterns: Vec<ConfigRegex>,
+    }
+
+    #[test]
+    fn test_config_regex_errors() {
+        let contents = "patterns = [\n  \"(a\",\n  \"b\",\n  \"c[\",\n]\n";
+        let parsed: Patterns = toml::from_str(contents).unwrap();
+
+        assert!(parsed.patterns[1].captures("b").is_some());
+        assert!(parsed.patterns[0].captures("a").is_none());
+
+        let mut errors = ConfigErrors::new(contents);
+        for (i, pattern) in parsed.patterns.iter().enumerate() {
+            pattern.validate(&format!("patterns[{i}]"), &mut errors);
+        }
+
+        let lines = errors
+            .errors
+            .iter()
+            .map(|(line, error)| (line.unwrap(), error.lines().next().unwrap().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (2, "patterns[0]: regex parse error:".to_string()),
+                (4, "patterns[2]: regex parse error:".to_string()),
+            ]
+        );
+        assert!(errors.into_result("config.toml").is_err());
+    }
+
+    #[test]
+    fn test_config_regex_anchored() {
+        let regex = ConfigRegex::new("a|b");
+        assert!(regex.anchored(true, true).captures("ab").is_none());
+        assert!(regex.anchored(true, false).captures("bc").is_some());
+        assert!(regex.anchored(true, false).captures("cb").is_none());
+        assert_eq!(regex.anchored(true, true).to_string(), "a|b");
+    }
+
+    #[test]
+    fn test_ordered() {
+        #[derive(Deserialize, Serialize)]
+        struct Tables {
+            #[serde(with = "ordered")]
+            tables: Vec<(String, i32)>,
+        }
+
+        let parsed: Tables = toml::from_str("[tables]\nz = 1\na = 2\nm = 3\n").unwrap();
+        let names = parsed
+            .tables
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["z", "a", "m"]);
+        assert!(toml::to_string(&parsed).unwrap().contains("z = 1"));
+    }
+}
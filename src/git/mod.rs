@@ -1,37 +1,111 @@
-use anyhow::Result;
-use std::process::Command;
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 mod git_ref;
+mod remote_url;
 
 pub use git_ref::GitRefField;
+pub use remote_url::RemoteUrl;
 
 pub struct Git {}
 
+/// A remote and its push url.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remote {
+    pub name: String,
+    pub url: String,
+}
+
 impl Git {
-    pub fn get_remote() -> Result<Option<String>> {
+    /// The remote named `name`, or if not given the first of `preferred` that exists,
+    /// the remote the current branch tracks or the first in alphabetical order.
+    pub fn get_remote(name: Option<&str>, preferred: &[String]) -> Result<Option<Remote>> {
+        let remotes = Self::get_remotes()?;
+
+        let tracking = if name.is_none() {
+            match Self::get_branch()? {
+                Some(branch) => Self::get_config(&format!("branch.{branch}.remote"))?,
+                None => None,
+            }
+        } else {
+            None
+        };
+
+        Self::pick_remote(remotes, name, preferred, tracking.as_deref())
+    }
+
+    fn pick_remote(
+        mut remotes: Vec<Remote>,
+        name: Option<&str>,
+        preferred: &[String],
+        tracking: Option<&str>,
+    ) -> Result<Option<Remote>> {
+        let position = |name: &str| remotes.iter().position(|r| r.name == name);
+
+        if let Some(name) = name {
+            let i = position(name).ok_or_else(|| anyhow!("No remote named `{name}`"))?;
+            return Ok(Some(remotes.swap_remove(i)));
+        }
+
+        let i = preferred
+            .iter()
+            .map(String::as_str)
+            .chain(tracking)
+            .find_map(position);
+        if let Some(i) = i {
+            return Ok(Some(remotes.swap_remove(i)));
+        }
+
+        remotes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(remotes.into_iter().next())
+    }
+
+    /// Every remote with its push url.
+    pub fn get_remotes() -> Result<Vec<Remote>> {
         let output = Command::new("git").args(["remote", "-v"]).output()?;
 
         if !output.status.success() {
-            return Ok(None);
+            return Ok(Vec::new());
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut remotes = Vec::new();
 
         for line in stdout.trim().lines() {
-            if let Some((_, rest)) = line.trim().split_once('\t') {
-                if let Some((remote, ty)) = rest.trim().split_once(' ') {
+            if let Some((name, rest)) = line.trim().split_once('\t') {
+                if let Some((url, ty)) = rest.trim().split_once(' ') {
                     if ty == "(push)" {
-                        return Ok(Some(remote.to_string()));
+                        remotes.push(Remote {
+                            name: name.to_string(),
+                            url: url.to_string(),
+                        });
                     }
                 }
             }
         }
 
-        Ok(None)
+        Ok(remotes)
+    }
+
+    pub fn get_config(key: &str) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .args(["config", "--get", key])
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!stdout.is_empty()).then_some(stdout))
     }
 
     pub fn rev_parse(text: &str) -> Result<Option<String>> {
-        let output = Command::new("git").args(["rev-parse", text]).output()?;
+        // Without `--verify` unknown options are echoed back as if they were revisions
+        let output = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", "--end-of-options", text])
+            .output()?;
 
         if !output.status.success() {
             return Ok(None);
@@ -41,6 +115,40 @@ impl Git {
         Ok(Some(stdout.trim().to_string()))
     }
 
+    /// The full name of the ref `text` refers to, e.g. `refs/tags/v1.0`.
+    pub fn full_ref_name(text: &str) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .args([
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                "--symbolic-full-name",
+                "--end-of-options",
+                text,
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!stdout.is_empty()).then_some(stdout))
+    }
+
+    /// Whether `path` is, or for a directory contains, a file tracked by git.
+    pub fn is_tracked(path: &Path) -> Result<bool> {
+        let status = Command::new("git")
+            .arg("--literal-pathspecs")
+            .args(["ls-files", "--error-unmatch", "--"])
+            .arg(path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        Ok(status.success())
+    }
+
     pub fn get_branch() -> Result<Option<String>> {
         let output = Command::new("git")
             .args(["branch", "--show-current"])
@@ -53,4 +161,54 @@ impl Git {
         let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
         Ok((!stdout.is_empty()).then_some(stdout))
     }
+
+    pub fn toplevel() -> Result<Option<PathBuf>> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!stdout.is_empty()).then(|| stdout.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remotes(names: &[&str]) -> Vec<Remote> {
+        names
+            .iter()
+            .map(|name| Remote {
+                name: name.to_string(),
+                url: format!("git@host:{name}/repo.git"),
+            })
+            .collect()
+    }
+
+    fn pick(name: Option<&str>, preferred: &[&str], tracking: Option<&str>) -> Option<String> {
+        let preferred = preferred.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let all = remotes(&["origin", "fork", "upstream"]);
+
+        Git::pick_remote(all, name, &preferred, tracking)
+            .unwrap()
+            .map(|r| r.name)
+    }
+
+    #[test]
+    fn test_pick_remote() {
+        assert_eq!(pick(Some("origin"), &["upstream"], None).unwrap(), "origin");
+        assert_eq!(
+            pick(None, &["nope", "upstream"], Some("fork")).unwrap(),
+            "upstream"
+        );
+        assert_eq!(pick(None, &["nope"], Some("origin")).unwrap(), "origin");
+        assert_eq!(pick(None, &[], None).unwrap(), "fork");
+        assert!(Git::pick_remote(remotes(&["origin"]), Some("nope"), &[], None).is_err());
+        assert_eq!(Git::pick_remote(Vec::new(), None, &[], None).unwrap(), None);
+    }
 }
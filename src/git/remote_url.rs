@@ -0,0 +1,184 @@
+/// A git remote url, split into its parts.
+///
+/// Understands `scheme://[user@]host[:port]/path` (ssh, git, http(s), file),
+/// scp-like `[user@]host:path` and plain local paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    pub scheme: String,
+    pub user: Option<String>,
+    /// `None` for local repositories
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    /// Without leading or trailing `/` and without `.git`
+    pub path: String,
+}
+
+impl RemoteUrl {
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = url.trim();
+        if url.is_empty() {
+            return None;
+        }
+
+        if let Some((scheme, rest)) = url.split_once("://") {
+            return Self::parse_with_scheme(scheme, rest);
+        }
+
+        // scp-like syntax, unless the colon comes after a slash: `./a:b`
+        if let Some((authority, path)) = url.split_once(':') {
+            if !authority.contains('/') && !authority.is_empty() {
+                let (user, host) = split_user(authority);
+                return Some(Self {
+                    scheme: "ssh".into(),
+                    user,
+                    host: Some(host.to_string()),
+                    port: None,
+                    path: clean_path(path),
+                });
+            }
+        }
+
+        Some(Self {
+            scheme: "file".into(),
+            user: None,
+            host: None,
+            port: None,
+            path: clean_path(url),
+        })
+    }
+
+    fn parse_with_scheme(scheme: &str, rest: &str) -> Option<Self> {
+        let scheme = match scheme.to_lowercase().as_str() {
+            "git+ssh" | "ssh+git" => "ssh".to_string(),
+            scheme => scheme.to_string(),
+        };
+
+        if scheme == "file" {
+            return Some(Self {
+                scheme,
+                user: None,
+                host: None,
+                port: None,
+                path: clean_path(rest),
+            });
+        }
+
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (user, host) = split_user(authority);
+        let (host, port) = match host.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port.parse().ok()?)),
+            None => (host, None),
+        };
+
+        if host.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            scheme,
+            user,
+            host: Some(host.to_string()),
+            port,
+            path: clean_path(path),
+        })
+    }
+
+    fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+
+    /// The first path segment, the user or organisation on most forges.
+    pub fn owner(&self) -> Option<&str> {
+        let segments = self.segments();
+        (segments.len() > 1).then(|| segments[0])
+    }
+
+    /// The last path segment.
+    pub fn repo(&self) -> Option<&str> {
+        self.segments().last().copied()
+    }
+
+    /// The segments between the owner and the repository, e.g. GitLab subgroups.
+    pub fn subgroups(&self) -> Vec<&str> {
+        let segments = self.segments();
+        if segments.len() < 3 {
+            return Vec::new();
+        }
+
+        segments[1..segments.len() - 1].to_vec()
+    }
+
+    /// Everything before the repository, the owner and any subgroups.
+    pub fn namespace(&self) -> Option<&str> {
+        self.path.rsplit_once('/').map(|(namespace, _)| namespace)
+    }
+}
+
+fn split_user(authority: &str) -> (Option<String>, &str) {
+    match authority.rsplit_once('@') {
+        Some((user, host)) => (Some(user.to_string()), host),
+        None => (None, authority),
+    }
+}
+
+fn clean_path(path: &str) -> String {
+    let path = path.trim_matches('/');
+    path.strip_suffix(".git").unwrap_or(path).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> RemoteUrl {
+        RemoteUrl::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_parse_scp_like() {
+        let url = parse("git@github.com:org/repo.git");
+
+        assert_eq!(url.scheme, "ssh");
+        assert_eq!(url.user.as_deref(), Some("git"));
+        assert_eq!(url.host.as_deref(), Some("github.com"));
+        assert_eq!(url.port, None);
+        assert_eq!(url.path, "org/repo");
+        assert_eq!(url.owner(), Some("org"));
+        assert_eq!(url.repo(), Some("repo"));
+    }
+
+    #[test]
+    fn test_parse_with_scheme() {
+        let url = parse("ssh://git@host:2222/org/repo");
+        assert_eq!(url.scheme, "ssh");
+        assert_eq!(url.host.as_deref(), Some("host"));
+        assert_eq!(url.port, Some(2222));
+        assert_eq!(url.path, "org/repo");
+
+        let url = parse("https://user@host/group/sub/deeper/repo.git/");
+        assert_eq!(url.scheme, "https");
+        assert_eq!(url.user.as_deref(), Some("user"));
+        assert_eq!(url.owner(), Some("group"));
+        assert_eq!(url.subgroups(), vec!["sub", "deeper"]);
+        assert_eq!(url.namespace(), Some("group/sub/deeper"));
+        assert_eq!(url.repo(), Some("repo"));
+
+        assert_eq!(parse("git+ssh://host/a/b").scheme, "ssh");
+        assert_eq!(parse("git://host/a/b").scheme, "git");
+        assert!(RemoteUrl::parse("https://host:port/a").is_none());
+    }
+
+    #[test]
+    fn test_parse_local() {
+        let url = parse("file:///srv/git/repo.git");
+        assert_eq!(url.scheme, "file");
+        assert_eq!(url.host, None);
+        assert_eq!(url.path, "srv/git/repo");
+
+        let url = parse("/srv/git/repo");
+        assert_eq!(url.scheme, "file");
+        assert_eq!(url.repo(), Some("repo"));
+
+        assert_eq!(parse("./a:b").scheme, "file");
+    }
+}
@@ -0,0 +1,100 @@
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display};
+
+/// A shell-like glob matched against `/` separated paths.
+///
+/// `*` matches within a path segment, `**` across segments, `?` one character.
+/// `dir/**` also matches `dir` itself.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    source: String,
+    regex: Regex,
+}
+
+impl Glob {
+    pub fn new(glob: &str) -> Self {
+        let mut regex = String::from("^");
+        let mut rest = glob;
+
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix("/**").filter(|a| a.is_empty()) {
+                regex.push_str("(?:/.*)?");
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("**/") {
+                regex.push_str("(?:.*/)?");
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("**") {
+                regex.push_str(".*");
+                rest = after;
+            } else {
+                match c {
+                    '*' => regex.push_str("[^/]*"),
+                    '?' => regex.push_str("[^/]"),
+                    c => regex.push_str(&regex::escape(&c.to_string())),
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        regex.push('$');
+
+        Self {
+            source: glob.to_string(),
+            regex: Regex::new(&regex).expect("escaped glob is a valid regex"),
+        }
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+}
+
+impl PartialEq for Glob {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for Glob {}
+
+impl Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for Glob {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|glob| Self::new(&glob))
+    }
+}
+
+impl Serialize for Glob {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.source.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let glob = Glob::new("services/*/src/**");
+        assert!(glob.is_match("services/billing/src"));
+        assert!(glob.is_match("services/billing/src/a/b.rs"));
+        assert!(!glob.is_match("services/billing/test"));
+        assert!(!glob.is_match("services/a/b/src"));
+
+        let glob = Glob::new("**/*.rs");
+        assert!(glob.is_match("a.rs"));
+        assert!(glob.is_match("a/b/c.rs"));
+        assert!(!glob.is_match("a/b/c.rsx"));
+
+        let glob = Glob::new("/home/?e/w.rk");
+        assert!(glob.is_match("/home/me/w.rk"));
+        assert!(!glob.is_match("/home/me/wark"));
+        assert!(!glob.is_match("/home/m/e/w.rk"));
+    }
+}
@@ -1,18 +1,23 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use branches::Branches;
-use clap::{arg, Command, CommandFactory, Parser, Subcommand};
-use clap_complete::{generate, Generator, Shell};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{generate, Shell};
 use log::error;
-use std::{io, path::PathBuf, process::ExitCode};
+use std::io::{self, BufRead, IsTerminal};
+use std::{path::PathBuf, process::ExitCode};
 use term_colors::TermColors;
 
 mod branches;
+mod clipboard;
 mod config;
 mod git;
+mod glob;
 mod open;
+mod picker;
 mod term_colors;
 
 use config::Config;
+use open::Emit;
 
 #[derive(Parser, Debug)]
 #[command(name = "bits")]
@@ -32,7 +37,53 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     Open {
-        text: String,
+        /// What to open, `-` (or no text when piped) reads one per line from stdin
+        text: Vec<String>,
+        /// Open the pull request for the current branch, same as the text `@pr`
+        #[arg(long, conflicts_with = "text")]
+        pr: bool,
+        /// Read the texts from the clipboard, one per line
+        #[arg(long, conflicts_with_all = ["text", "pr"])]
+        from_clipboard: bool,
+        /// Copy the resolved url to the clipboard, `--copy=also` opens it as well
+        #[arg(
+            long,
+            value_enum,
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "only",
+            conflicts_with_all = ["print", "all", "explain"]
+        )]
+        copy: Option<Copy>,
+        /// Print the resolved url instead of opening it
+        #[arg(long)]
+        print: bool,
+        /// Print every candidate of each text with its priority and rule
+        #[arg(long)]
+        all: bool,
+        /// Explain which rules matched and why
+        #[arg(long)]
+        explain: bool,
+        /// Open everything in the texts that a pattern matches, e.g. in a commit message
+        #[arg(long)]
+        extract: bool,
+        /// Git remote to use instead of the configured `remotes`
+        #[arg(long)]
+        remote: Option<String>,
+        /// How to hand over the url instead of the configured `launcher`,
+        /// `osc8` prints a terminal hyperlink
+        #[arg(long, value_enum)]
+        emit: Option<Emit>,
+        /// Pick one of the urls opened before to open again, the most frecent first.
+        /// Lists them with `--print` or when not on a terminal.
+        #[arg(long, conflicts_with_all = ["text", "pr", "from_clipboard", "extract"])]
+        recent: bool,
+        /// Do not add what is opened to the history
+        #[arg(long)]
+        no_history: bool,
+        /// List the aliases with their urls
+        #[arg(long, conflicts_with_all = ["text", "pr", "from_clipboard", "recent"])]
+        list_aliases: bool,
     },
     PrintColors,
     Completions {
@@ -46,13 +97,71 @@ pub enum Commands {
     },
 }
 
-fn print_completions<G: Generator>(generator: G, cmd: &mut Command) {
-    generate(
-        generator,
-        cmd,
-        cmd.get_name().to_string(),
-        &mut io::stdout(),
-    );
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Copy {
+    /// Copy instead of opening
+    Only,
+    /// Copy and open
+    Also,
+}
+
+/// The completion script for `shell`. Besides files it offers the aliases for
+/// the texts of `open`, as `bits open --list-aliases` lists them when completing.
+fn completions(shell: Shell) -> Result<String> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    generate(shell, &mut cmd, &name, &mut script);
+    let mut script = String::from_utf8(script)?;
+
+    let aliases = format!("{name} open --list-aliases 2>/dev/null | cut -f1");
+    match shell {
+        Shell::Bash => script.push_str(&format!(
+            r#"
+_{name}_open() {{
+    _{name} "$@"
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    if [[ " ${{COMP_WORDS[*]:1:COMP_CWORD-1}} " != *" open "* || ${{cur}} == -* ]]; then
+        return 0
+    fi
+    case "${{prev}}" in
+        -c|--config-file|--remote|--emit) return 0 ;;
+    esac
+    COMPREPLY=($(compgen -W "$({aliases})" -- "${{cur}}") $(compgen -f -- "${{cur}}"))
+}}
+
+complete -F _{name}_open -o bashdefault -o default {name}
+"#
+        )),
+        // `_{name}` shifts `words` for the subcommand, so they are kept first.
+        // Loaded from `fpath`, the first completion runs before this is defined.
+        Shell::Zsh => script.push_str(&format!(
+            r#"
+_{name}_open() {{
+    local -a args=("${{words[@]}}")
+    local current=$CURRENT open=${{words[(I)open]}} ret=1
+    _{name} "$@" && ret=0
+    if (( open > 1 && open < current )) && [[ ${{args[current]}} != -* ]]; then
+        case "${{args[current-1]}}" in
+            -c|--config-file|--remote|--emit) return ret ;;
+        esac
+        local -a aliases expl
+        aliases=(${{(f)"$({aliases})"}})
+        _wanted aliases expl alias compadd -a aliases && ret=0
+    fi
+    return ret
+}}
+
+compdef _{name}_open {name}
+"#
+        )),
+        Shell::Fish => script.push_str(&format!(
+            "complete -c {name} -n \"__fish_{name}_using_subcommand open\" -a \"({aliases})\"\n"
+        )),
+        _ => {}
+    }
+
+    Ok(script)
 }
 
 fn main() -> ExitCode {
@@ -68,22 +177,114 @@ fn main() -> ExitCode {
 }
 
 pub fn run(args: Cli) -> Result<()> {
-    let config = Config::new(args.config_file)?;
+    let mut config = Config::new(args.config_file)?;
 
     match args.command {
-        Commands::Open { text } => config.open.open(&text),
+        Commands::Open {
+            text,
+            print,
+            all,
+            explain,
+            extract,
+            remote,
+            pr,
+            from_clipboard,
+            copy,
+            emit,
+            recent,
+            no_history,
+            list_aliases,
+        } => {
+            config.open.set_remote(remote);
+            config.open.set_emit(emit);
+            config.open.set_history(!no_history);
+
+            if recent {
+                return config.open.recent(print || all);
+            }
+            if list_aliases {
+                return config.open.list_aliases();
+            }
+
+            let mut texts = if pr {
+                vec![open::PR_TEXT.to_string()]
+            } else if from_clipboard {
+                config.open.paste()?
+            } else {
+                read_texts(text)?
+            };
+
+            if extract {
+                texts = config.open.extract(&texts)?;
+                if texts.is_empty() {
+                    return Err(anyhow!("Nothing to open found in the text"));
+                }
+            }
+
+            if explain {
+                config.open.explain(&texts)
+            } else if print || all {
+                config.open.print(&texts, all)
+            } else if let Some(copy) = copy {
+                config.open.copy(&texts, copy == Copy::Also)
+            } else {
+                config.open.open(&texts)
+            }
+        }
         Commands::PrintColors => TermColors::print_colors(),
         Commands::Branches {
             author,
             include_remotes,
         } => Branches::list(&author, include_remotes),
         Commands::Completions { shell } => {
-            print_completions(shell, &mut Cli::command());
+            print!("{}", completions(shell)?);
             Ok(())
         }
     }
 }
 
+/// Replace `-`, or no texts at all when stdin is piped, with the lines of stdin.
+fn read_texts(texts: Vec<String>) -> Result<Vec<String>> {
+    let stdin = io::stdin();
+    if texts.is_empty() && stdin.is_terminal() {
+        return Err(anyhow!(
+            "Nothing to open, give a text or pipe them to stdin"
+        ));
+    }
+
+    if !texts.is_empty() && !texts.iter().any(|t| t == "-") {
+        return Ok(texts);
+    }
+
+    expand_texts(texts, stdin.lock())
+}
+
+/// Replace `-`, or no texts at all, with the non-empty lines of `input`.
+fn expand_texts(texts: Vec<String>, input: impl BufRead) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            lines.push(line.trim().to_string());
+        }
+    }
+
+    if texts.is_empty() {
+        return Ok(lines);
+    }
+
+    let mut all = Vec::new();
+    for text in texts {
+        if text == "-" {
+            all.extend(lines.iter().cloned());
+        } else {
+            all.push(text);
+        }
+    }
+
+    Ok(all)
+}
+
 fn init_log(verbosity: u8) {
     let mut logger = env_logger::builder();
     logger.parse_default_env().format_timestamp_secs();
@@ -106,3 +307,74 @@ fn init_log(verbosity: u8) {
 
     logger.init();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_copy(args: &[&str]) -> (Option<Copy>, Vec<String>) {
+        let cli = Cli::try_parse_from(["bits", "open"].iter().chain(args)).unwrap();
+        match cli.command {
+            Commands::Open { copy, text, .. } => (copy, text),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_parse_copy() {
+        assert_eq!(
+            parse_copy(&["--copy", "TEST-1"]),
+            (Some(Copy::Only), vec!["TEST-1".to_string()])
+        );
+        assert_eq!(
+            parse_copy(&["--copy=also", "TEST-1"]),
+            (Some(Copy::Also), vec!["TEST-1".to_string()])
+        );
+        assert_eq!(
+            parse_copy(&["TEST-1", "--copy"]),
+            (Some(Copy::Only), vec!["TEST-1".to_string()])
+        );
+        assert_eq!(parse_copy(&["TEST-1"]), (None, vec!["TEST-1".to_string()]));
+        for other in ["--print", "--all", "--explain"] {
+            assert!(Cli::try_parse_from(["bits", "open", "--copy", other, "12"]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_expand_texts() {
+        let texts = |texts: &[&str]| texts.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        let input = "TEST-1\n\n  TEST-2  \n".as_bytes();
+
+        assert_eq!(
+            expand_texts(texts(&[]), input).unwrap(),
+            texts(&["TEST-1", "TEST-2"])
+        );
+        assert_eq!(
+            expand_texts(texts(&["a", "-", "b"]), input).unwrap(),
+            texts(&["a", "TEST-1", "TEST-2", "b"])
+        );
+        assert_eq!(
+            expand_texts(texts(&["-", "-"]), input).unwrap(),
+            texts(&["TEST-1", "TEST-2", "TEST-1", "TEST-2"])
+        );
+    }
+
+    #[test]
+    fn test_completions() {
+        let aliases = "$(bits open --list-aliases 2>/dev/null | cut -f1)";
+
+        let bash = completions(Shell::Bash).unwrap();
+        assert!(bash.contains(&format!(r#"$(compgen -W "{aliases}" -- "${{cur}}")"#)));
+        assert!(bash.ends_with("complete -F _bits_open -o bashdefault -o default bits\n"));
+
+        let zsh = completions(Shell::Zsh).unwrap();
+        assert!(zsh.contains(&format!(r#"aliases=(${{(f)"{aliases}"}})"#)));
+        assert!(zsh.ends_with("compdef _bits_open bits\n"));
+
+        let fish = completions(Shell::Fish).unwrap();
+        assert!(fish.ends_with(&format!(
+            "complete -c bits -n \"__fish_bits_using_subcommand open\" -a \"({})\"\n",
+            &aliases[2..aliases.len() - 1]
+        )));
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The urls opened so far, oldest first.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default, rename = "entry")]
+    entries: Vec<Entry>,
+}
+
+/// One url that was opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// The text given to `open`
+    pub text: String,
+    pub url: String,
+    /// Config path of the rule that produced the url
+    pub rule: String,
+    /// Root of the repository `open` ran in
+    pub repo: Option<String>,
+    /// Seconds since the Unix epoch
+    pub time: u64,
+}
+
+/// A url of the history with how often and how recently it was opened.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Recent<'a> {
+    /// The latest time it was opened
+    pub entry: &'a Entry,
+    pub visits: usize,
+    pub score: u64,
+}
+
+impl History {
+    /// `history.toml` in the data directory, e.g. `~/.local/share/bits/`.
+    pub fn path() -> Result<PathBuf> {
+        let mut path =
+            dirs::data_dir().ok_or_else(|| anyhow!("Could not find the data directory"))?;
+        path.push("bits/history.toml");
+        Ok(path)
+    }
+
+    /// The history in `path`, empty if there is none yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(anyhow!("Could not read {}: {e}", path.display())),
+        };
+
+        toml::from_str(&contents).map_err(|e| anyhow!("Could not parse {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)
+            .map_err(|e| anyhow!("Could not write {}: {e}", path.display()))
+    }
+
+    /// Add `entries`, keeping only the latest `size`.
+    pub fn push(&mut self, entries: impl IntoIterator<Item = Entry>, size: usize) {
+        self.entries.extend(entries);
+        let excess = self.entries.len().saturating_sub(size);
+        self.entries.drain(..excess);
+    }
+
+    /// Every url once, the most frecent first: each visit counts for less the
+    /// older it is, ties go to the one opened last.
+    pub fn recent(&self, now: u64) -> Vec<Recent<'_>> {
+        let mut recent: Vec<Recent> = Vec::new();
+
+        for entry in &self.entries {
+            let score = weight(now.saturating_sub(entry.time));
+            match recent.iter_mut().find(|r| r.entry.url == entry.url) {
+                Some(r) => {
+                    if entry.time >= r.entry.time {
+                        r.entry = entry;
+                    }
+                    r.visits += 1;
+                    r.score += score;
+                }
+                None => recent.push(Recent {
+                    entry,
+                    visits: 1,
+                    score,
+                }),
+            }
+        }
+
+        recent.sort_by(|a, b| b.score.cmp(&a.score).then(b.entry.time.cmp(&a.entry.time)));
+        recent
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// What a visit `age` seconds ago adds to the score.
+fn weight(age: u64) -> u64 {
+    match age / (24 * 60 * 60) {
+        0..4 => 100,
+        4..14 => 70,
+        14..31 => 50,
+        31..90 => 30,
+        _ => 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn entry(url: &str, time: u64) -> Entry {
+        Entry {
+            text: url.to_uppercase(),
+            url: url.into(),
+            rule: "open.patterns[0]".into(),
+            repo: None,
+            time,
+        }
+    }
+
+    #[test]
+    fn test_recent() {
+        let now = 100 * DAY;
+        let mut history = History::default();
+        history.push(
+            [
+                entry("a", now - 60 * DAY),
+                entry("a", now - 50 * DAY),
+                entry("a", now - 40 * DAY),
+                entry("b", now - DAY),
+                entry("c", now - 2 * DAY),
+                entry("c", now - 20 * DAY),
+            ],
+            10,
+        );
+
+        let recent = history
+            .recent(now)
+            .iter()
+            .map(|r| (r.entry.url.as_str(), r.visits, r.score))
+            .collect::<Vec<_>>();
+        assert_eq!(recent, vec![("c", 2, 150), ("b", 1, 100), ("a", 3, 90)]);
+        assert_eq!(history.recent(now)[0].entry.time, now - 2 * DAY);
+    }
+
+    #[test]
+    fn test_push_and_save() {
+        let mut history = History::default();
+        history.push((0..5).map(|i| entry(&i.to_string(), i)), 3);
+        history.push([entry("5", 5)], 3);
+        let urls = history
+            .entries
+            .iter()
+            .map(|e| e.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(urls, vec!["3", "4", "5"]);
+
+        let path = std::env::temp_dir().join(format!("bits-history-{}.toml", std::process::id()));
+        assert_eq!(History::load(&path).unwrap(), History::default());
+        history.save(&path).unwrap();
+        assert_eq!(History::load(&path).unwrap(), history);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,484 @@
+use regex::Regex;
+use std::fmt::{self, Display};
+use std::path::Path;
+
+use crate::git::Git;
+use crate::open::template::Vars;
+
+/// What the text given to `open` refers to in the current repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitInput {
+    /// `.`, the repository itself
+    Repo { branch: Option<String> },
+    /// A local or remote-tracking branch
+    Branch(BranchRef),
+    /// A tag
+    Tag { tag: String, commit: String },
+    /// A commit, with its full hash
+    Commit(String),
+    /// `base..head` or `base...head`
+    Range(RevRange),
+    /// A file inside the repository
+    File(RepoFile),
+    /// `@pr`, the pull request for the current branch
+    PullRequest(BranchPr),
+    /// Anything else, matched against the patterns
+    Text(String),
+}
+
+/// A file inside the repository, optionally with a line range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoFile {
+    /// Relative to the repository root, always separated by `/`
+    pub path: String,
+    pub lines: Option<(u32, u32)>,
+    /// The commit `HEAD` points to
+    pub commit: String,
+    pub branch: Option<String>,
+}
+
+/// A branch given by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchRef {
+    /// The name on the remote, without the remote for a remote-tracking branch
+    pub branch: String,
+    /// The remote of a remote-tracking branch
+    pub remote: Option<String>,
+    pub commit: String,
+}
+
+/// Two revisions to compare.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevRange {
+    /// The names as the forge knows them: branches and tags by name, others as a commit hash
+    pub base: String,
+    pub head: String,
+    /// `..` or `...`
+    pub dots: String,
+}
+
+/// The current branch and what is known about its pull request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchPr {
+    pub branch: String,
+    /// The branch name on the remote, the local name if it has no upstream
+    pub upstream: String,
+    /// Known if the upstream is a pull request ref, as set up by e.g. `gh pr checkout`
+    pub number: Option<u64>,
+}
+
+/// The text that refers to the pull request for the current branch.
+pub const PR_TEXT: &str = "@pr";
+
+impl GitInput {
+    pub fn classify(text: &str) -> Self {
+        if text == "." {
+            return Self::Repo {
+                branch: Git::get_branch().ok().flatten(),
+            };
+        }
+
+        if text == PR_TEXT {
+            if let Some(pr) = BranchPr::current() {
+                return Self::PullRequest(pr);
+            }
+        }
+
+        if let Some(file) = RepoFile::find(text) {
+            return Self::File(file);
+        }
+
+        if let Some(range) = RevRange::find(text) {
+            return Self::Range(range);
+        }
+
+        let Some(commit) = Git::rev_parse(&format!("{text}^{{commit}}")).ok().flatten() else {
+            return Self::Text(text.to_string());
+        };
+
+        let full_name = Git::full_ref_name(text).ok().flatten();
+        match full_name
+            .as_deref()
+            .and_then(|name| RefName::parse(name, text))
+        {
+            Some(RefName::Branch { branch, remote }) => Self::Branch(BranchRef {
+                branch,
+                remote,
+                commit,
+            }),
+            Some(RefName::Tag(tag)) => Self::Tag { tag, commit },
+            None => Self::Commit(commit),
+        }
+    }
+
+    /// The variables describing the input, other than the file and pull request ones.
+    pub fn vars(&self) -> Vars {
+        let mut vars = Vars::new();
+        match self {
+            Self::Branch(branch) => {
+                vars.insert("branch".into(), branch.branch.clone());
+                vars.insert("commit".into(), branch.commit.clone());
+                if let Some(remote) = &branch.remote {
+                    vars.insert("remote".into(), remote.clone());
+                }
+            }
+            Self::Tag { tag, commit } => {
+                vars.insert("tag".into(), tag.clone());
+                vars.insert("commit".into(), commit.clone());
+            }
+            Self::Range(range) => {
+                vars.insert("base".into(), range.base.clone());
+                vars.insert("head".into(), range.head.clone());
+                vars.insert("dots".into(), range.dots.clone());
+            }
+            _ => {}
+        }
+
+        vars
+    }
+}
+
+/// What a full ref name says about the text it was resolved from.
+#[derive(Debug, PartialEq, Eq)]
+enum RefName {
+    Branch {
+        branch: String,
+        remote: Option<String>,
+    },
+    Tag(String),
+}
+
+impl RefName {
+    /// Only a ref `text` names itself, `HEAD` resolves to a branch but is not one.
+    fn parse(full_name: &str, text: &str) -> Option<Self> {
+        let names_itself = full_name == text || full_name.ends_with(&format!("/{text}"));
+        if !names_itself {
+            return None;
+        }
+
+        if let Some(branch) = full_name.strip_prefix("refs/heads/") {
+            return Some(Self::Branch {
+                branch: branch.to_string(),
+                remote: None,
+            });
+        }
+        if let Some(remote_branch) = full_name.strip_prefix("refs/remotes/") {
+            let (remote, branch) = remote_branch.split_once('/')?;
+            return Some(Self::Branch {
+                branch: branch.to_string(),
+                remote: Some(remote.to_string()),
+            });
+        }
+
+        full_name
+            .strip_prefix("refs/tags/")
+            .map(|tag| Self::Tag(tag.to_string()))
+    }
+}
+
+impl RevRange {
+    fn find(text: &str) -> Option<Self> {
+        let (base, head, dots) = split_range(text)?;
+        Some(Self {
+            base: Self::name(base)?,
+            head: Self::name(head)?,
+            dots: dots.to_string(),
+        })
+    }
+
+    /// Branches and tags by name, anything else as a commit hash.
+    fn name(rev: &str) -> Option<String> {
+        let commit = Git::rev_parse(&format!("{rev}^{{commit}}"))
+            .ok()
+            .flatten()?;
+        let full_name = Git::full_ref_name(rev).ok().flatten();
+
+        match full_name
+            .as_deref()
+            .and_then(|name| RefName::parse(name, rev))
+        {
+            Some(RefName::Branch { branch, .. }) => Some(branch),
+            Some(RefName::Tag(tag)) => Some(tag),
+            None => Some(commit),
+        }
+    }
+}
+
+impl Display for GitInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Repo { branch: Some(b) } => write!(f, "the repository, on branch {b}"),
+            Self::Repo { branch: None } => write!(f, "the repository"),
+            Self::Branch(BranchRef {
+                branch,
+                remote: Some(remote),
+                ..
+            }) => write!(f, "branch {branch} of remote {remote}"),
+            Self::Branch(branch) => write!(f, "branch {}", branch.branch),
+            Self::Tag { tag, .. } => write!(f, "tag {tag}"),
+            Self::Commit(commit) => write!(f, "commit {commit} (git rev-parse)"),
+            Self::Range(range) => {
+                write!(
+                    f,
+                    "range from {} to {} ({})",
+                    range.base, range.head, range.dots
+                )
+            }
+            Self::File(file) => match file.lines {
+                Some((start, end)) => write!(f, "file {} lines {start}-{end}", file.path),
+                None => write!(f, "file {}", file.path),
+            },
+            Self::PullRequest(pr) => match pr.number {
+                Some(n) => write!(f, "pull request {n} for branch {}", pr.branch),
+                None => write!(f, "new pull request for branch {}", pr.upstream),
+            },
+            Self::Text(_) => write!(f, "text"),
+        }
+    }
+}
+
+impl RepoFile {
+    /// Parse `path[:start[-end]]` where path exists inside the repository
+    /// and is tracked, untracked and ignored files have no url on the remote.
+    fn find(text: &str) -> Option<Self> {
+        let root = Git::toplevel().ok().flatten()?;
+        let (path, lines) = locate(text, &root, |path| Git::is_tracked(path).unwrap_or(false))?;
+
+        Some(Self {
+            path,
+            lines,
+            commit: Git::rev_parse("HEAD").ok().flatten()?,
+            branch: Git::get_branch().ok().flatten(),
+        })
+    }
+
+    /// `<path>`, `<commit>`, `<branch>` if on a branch, `<ref>` (the branch,
+    /// or the commit if detached) and `<start>`, `<end>` if lines were given.
+    pub fn vars(&self) -> Vars {
+        let mut vars = Vars::new();
+        vars.insert("path".into(), self.path.clone());
+        vars.insert("commit".into(), self.commit.clone());
+        vars.insert(
+            "ref".into(),
+            self.branch.clone().unwrap_or_else(|| self.commit.clone()),
+        );
+        if let Some(branch) = &self.branch {
+            vars.insert("branch".into(), branch.clone());
+        }
+        if let Some((start, end)) = self.lines {
+            vars.insert("start".into(), start.to_string());
+            vars.insert("end".into(), end.to_string());
+        }
+
+        vars
+    }
+}
+
+impl BranchPr {
+    fn current() -> Option<Self> {
+        let branch = Git::get_branch().ok().flatten()?;
+        let merge = Git::get_config(&format!("branch.{branch}.merge"))
+            .ok()
+            .flatten();
+
+        Some(Self::from_merge_ref(branch, merge.as_deref()))
+    }
+
+    /// Use the upstream ref `merge` (`branch.<name>.merge`) of `branch`.
+    fn from_merge_ref(branch: String, merge: Option<&str>) -> Self {
+        let regex = Regex::new(r"^refs/(?:pull|merge-requests)/(\d+)/head$").unwrap();
+        let number = merge
+            .and_then(|merge| regex.captures(merge))
+            .and_then(|groups| groups[1].parse().ok());
+
+        let upstream = merge
+            .and_then(|merge| merge.strip_prefix("refs/heads/"))
+            .map(str::to_string)
+            .unwrap_or_else(|| branch.clone());
+
+        Self {
+            branch,
+            upstream,
+            number,
+        }
+    }
+
+    /// `<branch>`, `<upstream>` and `<pr>` if the number is known.
+    pub fn vars(&self) -> Vars {
+        let mut vars = Vars::new();
+        vars.insert("branch".into(), self.branch.clone());
+        vars.insert("upstream".into(), self.upstream.clone());
+        if let Some(number) = self.number {
+            vars.insert("pr".into(), number.to_string());
+        }
+
+        vars
+    }
+}
+
+/// Split `base..head` or `base...head`, both sides are required.
+fn split_range(text: &str) -> Option<(&str, &str, &str)> {
+    let (base, head, dots) = match text.split_once("...") {
+        Some((base, head)) => (base, head, "..."),
+        None => {
+            let (base, head) = text.split_once("..")?;
+            (base, head, "..")
+        }
+    };
+
+    (!base.is_empty() && !head.is_empty() && !head.starts_with('.')).then_some((base, head, dots))
+}
+
+/// The path of `text` relative to `root`, separated by `/`, and its lines,
+/// if it exists inside `root` and `is_tracked` says so.
+fn locate(
+    text: &str,
+    root: &Path,
+    is_tracked: impl Fn(&Path) -> bool,
+) -> Option<(String, Option<(u32, u32)>)> {
+    let (path, lines) = split_lines(text);
+
+    let path = Path::new(path).canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+    let relative = path.strip_prefix(&root).ok()?;
+    if !is_tracked(&path) {
+        return None;
+    }
+
+    let relative = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some((relative, lines))
+}
+
+/// Split `path:start-end` or `path:line` into the path and line range.
+fn split_lines(text: &str) -> (&str, Option<(u32, u32)>) {
+    let regex = Regex::new(r"^(.+?):(\d+)(?:-(\d+))?$").unwrap();
+    let Some(groups) = regex.captures(text) else {
+        return (text, None);
+    };
+
+    let path = groups.get(1).map_or(text, |g| g.as_str());
+    let start = groups[2].parse().ok();
+    let end = groups.get(3).and_then(|g| g.as_str().parse().ok());
+
+    match (start, end) {
+        (Some(start), Some(end)) => (path, Some((start, end))),
+        (Some(start), None) => (path, Some((start, start))),
+        _ => (text, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(split_lines("src/a.rs"), ("src/a.rs", None));
+        assert_eq!(split_lines("src/a.rs:40"), ("src/a.rs", Some((40, 40))));
+        assert_eq!(split_lines("src/a.rs:40-60"), ("src/a.rs", Some((40, 60))));
+        assert_eq!(split_lines("src/a.rs:"), ("src/a.rs:", None));
+        assert_eq!(split_lines("a:b:3"), ("a:b", Some((3, 3))));
+    }
+
+    #[test]
+    fn test_split_range() {
+        assert_eq!(split_range("main..feat"), Some(("main", "feat", "..")));
+        assert_eq!(split_range("v1.0...v1.1"), Some(("v1.0", "v1.1", "...")));
+        assert_eq!(split_range("main.."), None);
+        assert_eq!(split_range("..feat"), None);
+        assert_eq!(split_range("a....b"), None);
+        assert_eq!(split_range("v1.0"), None);
+    }
+
+    #[test]
+    fn test_ref_name() {
+        let branch = |branch: &str, remote: Option<&str>| RefName::Branch {
+            branch: branch.into(),
+            remote: remote.map(str::to_string),
+        };
+
+        assert_eq!(
+            RefName::parse("refs/heads/feat/a", "feat/a"),
+            Some(branch("feat/a", None))
+        );
+        assert_eq!(
+            RefName::parse("refs/remotes/origin/feat/a", "origin/feat/a"),
+            Some(branch("feat/a", Some("origin")))
+        );
+        assert_eq!(
+            RefName::parse("refs/tags/v1.0", "v1.0"),
+            Some(RefName::Tag("v1.0".into()))
+        );
+        assert_eq!(
+            RefName::parse("refs/tags/v1.0", "refs/tags/v1.0"),
+            Some(RefName::Tag("v1.0".into()))
+        );
+        assert_eq!(RefName::parse("refs/heads/main", "HEAD"), None);
+        assert_eq!(RefName::parse("refs/heads/main", "main~1"), None);
+    }
+
+    #[test]
+    fn test_branch_pr() {
+        let pr = BranchPr::from_merge_ref("feat".into(), Some("refs/heads/user/feat"));
+        assert_eq!(pr.upstream, "user/feat");
+        assert_eq!(pr.number, None);
+
+        let pr = BranchPr::from_merge_ref("pr-12".into(), Some("refs/pull/12/head"));
+        assert_eq!(pr.upstream, "pr-12");
+        assert_eq!(pr.number, Some(12));
+
+        let pr = BranchPr::from_merge_ref("mr".into(), Some("refs/merge-requests/3/head"));
+        assert_eq!(pr.number, Some(3));
+
+        let pr = BranchPr::from_merge_ref("local".into(), None);
+        assert_eq!(pr.upstream, "local");
+        assert_eq!(pr.number, None);
+    }
+
+    #[test]
+    fn test_locate_tracked_only() {
+        let root = std::env::temp_dir().join(format!("bits-locate-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        let root = root.canonicalize().unwrap();
+        let ignored = root.join("target");
+        let locate = |text: &str| locate(text, &root, |path| !path.starts_with(&ignored));
+
+        assert_eq!(
+            locate(&format!("{}/src/main.rs:3", root.display())),
+            Some(("src/main.rs".to_string(), Some((3, 3))))
+        );
+        assert_eq!(
+            locate(&format!("{}/src", root.display())),
+            Some(("src".to_string(), None))
+        );
+        assert_eq!(locate(&format!("{}/target", root.display())), None);
+        assert_eq!(locate(&format!("{}/nope.rs", root.display())), None);
+        assert_eq!(locate(&std::env::temp_dir().display().to_string()), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_file_vars() {
+        let file = RepoFile {
+            path: "src/a.rs".into(),
+            lines: Some((1, 2)),
+            commit: "abc".into(),
+            branch: None,
+        };
+
+        let vars = file.vars();
+        assert_eq!(vars["ref"], "abc");
+        assert_eq!(vars["start"], "1");
+        assert_eq!(vars["end"], "2");
+        assert!(!vars.contains_key("branch"));
+    }
+}
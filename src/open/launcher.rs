@@ -0,0 +1,355 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{self, PathBuf};
+use std::process::{self, Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{env, io};
+
+use crate::config::ConfigErrors;
+use crate::open::path::{expand_env, expand_path};
+use crate::open::template::{Template, Vars};
+use crate::open::when::{self, When};
+
+/// The program that opens a url, with its arguments.
+///
+/// `program` and `cwd` can use environment variables, `$EDITOR` or `${EDITOR}`,
+/// and `cwd` can start with `~`.
+/// The arguments, environment and `cwd` are templates with the variables of
+/// the rule that matched and `<url>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Launcher {
+    program: String,
+    #[serde(default = "default_args")]
+    args: Vec<Template>,
+    #[serde(default)]
+    env: BTreeMap<String, Template>,
+    cwd: Option<Template>,
+    /// Run attached to the terminal and wait for it to exit, e.g. for an editor
+    #[serde(default)]
+    terminal: bool,
+    /// Overrides `launch` of `[open]`
+    mode: Option<LaunchMode>,
+    /// Overrides `launch_timeout` of `[open]`, in seconds
+    timeout: Option<u64>,
+    /// Only used when this holds, `program` is used otherwise
+    when: Option<When>,
+}
+
+/// How long to watch the launcher before considering it started.
+/// `monitor` and `wait` capture stderr in a temporary file, which a launcher
+/// that keeps running goes on writing to after it was removed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchMode {
+    /// Start it and move on, only failing to start is reported
+    #[default]
+    Detach,
+    /// Leave it running, but report it exiting with an error right away
+    Monitor,
+    /// Wait for it to exit, up to the timeout, and report a failure
+    Wait,
+}
+
+/// How long `monitor` watches the launcher for an early exit.
+const MONITOR_GRACE: Duration = Duration::from_millis(300);
+
+fn default_args() -> Vec<Template> {
+    vec![Template::new("<url>")]
+}
+
+impl Launcher {
+    /// Run `program <url>`.
+    pub fn new(program: &str) -> Self {
+        Self {
+            program: program.to_string(),
+            args: default_args(),
+            env: BTreeMap::new(),
+            cwd: None,
+            terminal: false,
+            mode: None,
+            timeout: None,
+            when: None,
+        }
+    }
+
+    /// The command for `url`, not started yet.
+    pub fn command(&self, url: &str, vars: &Vars) -> Result<Command> {
+        let mut vars = vars.clone();
+        vars.insert("url".into(), url.to_string());
+
+        // A bare name is looked up on PATH, never in the current directory
+        let program = PathBuf::from(expand_env(&self.program)?);
+        let program = if program.to_string_lossy().contains(path::is_separator) {
+            program.canonicalize().unwrap_or(program)
+        } else {
+            program
+        };
+
+        let mut command = Command::new(program);
+        for arg in &self.args {
+            command.arg(arg.render(&vars)?);
+        }
+        for (name, value) in &self.env {
+            command.env(name, value.render(&vars)?);
+        }
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(expand_path(&cwd.render(&vars)?)?);
+        }
+
+        if !self.terminal {
+            command.stdin(Stdio::null()).stdout(Stdio::null());
+        }
+
+        Ok(command)
+    }
+
+    /// Start the command for `url` and watch it as `mode` says, unless the
+    /// launcher sets its own mode. A launcher using the terminal is always waited for.
+    pub fn launch(&self, url: &str, vars: &Vars, mode: LaunchMode, timeout: u64) -> Result<()> {
+        let mut command = self.command(url, vars)?;
+        let line = command_line(&command);
+
+        if self.terminal {
+            let status = command
+                .status()
+                .map_err(|e| anyhow!("Could not run `{line}`: {e}"))?;
+            return check_status(&line, status, "");
+        }
+
+        let mode = self.mode.unwrap_or(mode);
+        if mode == LaunchMode::Detach {
+            command.stderr(Stdio::null());
+            command
+                .spawn()
+                .map_err(|e| anyhow!("Could not run `{line}`: {e}"))?;
+            return Ok(());
+        }
+
+        // A file rather than a pipe, the launcher may outlive us and keep writing
+        let log = StderrLog::new()?;
+        command.stderr(log.stdio()?);
+        let mut child = command
+            .spawn()
+            .map_err(|e| anyhow!("Could not run `{line}`: {e}"))?;
+
+        let limit = match mode {
+            LaunchMode::Wait => Duration::from_secs(self.timeout.unwrap_or(timeout)),
+            _ => MONITOR_GRACE,
+        };
+
+        match wait_timeout(&mut child, limit)? {
+            Some(status) => check_status(&line, status, &log.read()),
+            None if mode == LaunchMode::Wait => {
+                warn!("`{line}` is still running after {}s", limit.as_secs());
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The command line for `url`, as it would be run.
+    pub fn display(&self, url: &str, vars: &Vars) -> Result<String> {
+        Ok(command_line(&self.command(url, vars)?))
+    }
+
+    /// Whether the launcher can be used in this invocation.
+    pub fn applies(&self) -> bool {
+        when::holds(&self.when)
+    }
+
+    pub fn validate(&self, path: &str, errors: &mut ConfigErrors) {
+        for (i, arg) in self.args.iter().enumerate() {
+            arg.validate(&format!("{path}.args[{i}]"), errors);
+        }
+        for (name, value) in &self.env {
+            value.validate(&format!("{path}.env.{name}"), errors);
+        }
+        if let Some(cwd) = &self.cwd {
+            cwd.validate(&format!("{path}.cwd"), errors);
+        }
+        if let Some(when) = &self.when {
+            when.validate(&format!("{path}.when"), errors);
+        }
+    }
+}
+
+/// Wait for `child` to exit for at most `limit`, `None` if it is still running.
+fn wait_timeout(child: &mut Child, limit: Duration) -> Result<Option<ExitStatus>> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= limit {
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn check_status(line: &str, status: ExitStatus, stderr: &str) -> Result<()> {
+    match (status.success(), stderr.trim()) {
+        (true, _) => Ok(()),
+        (false, "") => Err(anyhow!("`{line}` failed with {status}")),
+        (false, stderr) => Err(anyhow!("`{line}` failed with {status}:\n{stderr}")),
+    }
+}
+
+/// The program and its arguments, quoted where needed.
+fn command_line(command: &Command) -> String {
+    let program = command.get_program().to_string_lossy();
+    let args = command.get_args().map(|arg| arg.to_string_lossy());
+
+    std::iter::once(program)
+        .chain(args)
+        .map(|part| {
+            if part.is_empty() || part.contains(|c: char| c.is_whitespace() || "'\"\\$".contains(c))
+            {
+                format!("'{}'", part.replace('\'', "'\\''"))
+            } else {
+                part.into_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A temporary file that receives the stderr of a launcher. It is always
+/// a new file only we can read, never one that was already there.
+struct StderrLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl StderrLog {
+    fn new() -> Result<Self> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("bits-open-{}-{n}.log", process::id()));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let file = options
+            .open(&path)
+            .map_err(|e| anyhow!("Could not create {}: {e}", path.display()))?;
+
+        Ok(Self { path, file })
+    }
+
+    fn stdio(&self) -> io::Result<Stdio> {
+        self.file.try_clone().map(Stdio::from)
+    }
+
+    fn read(&self) -> String {
+        fs::read_to_string(&self.path).unwrap_or_default()
+    }
+}
+
+impl Drop for StderrLog {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_launch() {
+        let sh = |script: &str| Launcher {
+            args: vec![Template::new("-c"), Template::new(script)],
+            ..Launcher::new("sh")
+        };
+        let vars = Vars::new();
+
+        for mode in [LaunchMode::Monitor, LaunchMode::Wait] {
+            assert!(sh("exit 0").launch("", &vars, mode, 5).is_ok());
+
+            let e = sh("echo oops >&2; exit 3")
+                .launch("", &vars, mode, 5)
+                .unwrap_err()
+                .to_string();
+            assert!(
+                e.starts_with("`sh -c 'echo oops >&2; exit 3'` failed with"),
+                "{e}"
+            );
+            assert!(e.ends_with("\noops"), "{e}");
+        }
+
+        assert!(sh("exit 3")
+            .launch("", &vars, LaunchMode::Detach, 5)
+            .is_ok());
+        assert!(sh("sleep 1; exit 3")
+            .launch("", &vars, LaunchMode::Monitor, 5)
+            .is_ok());
+        assert!(sh("sleep 1; exit 3")
+            .launch("", &vars, LaunchMode::Wait, 5)
+            .is_err());
+        assert!(Launcher::new("/nonexistent/bits")
+            .launch("", &vars, LaunchMode::Detach, 5)
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stderr_log() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let log = StderrLog::new().unwrap();
+        let mode = fs::metadata(&log.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let path = log.path.clone();
+        drop(log);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_display() {
+        let launcher = Launcher {
+            args: vec![Template::new("+<line>"), Template::new("<path>")],
+            ..Launcher::new("vi")
+        };
+        let vars = Vars::from([
+            ("path".to_string(), "src/a.rs".to_string()),
+            ("line".to_string(), "12".to_string()),
+        ]);
+
+        assert_eq!(launcher.display("", &vars).unwrap(), "vi +12 src/a.rs");
+        assert_eq!(
+            Launcher::new("vi").display("a b'c", &vars).unwrap(),
+            "vi 'a b'\\''c'"
+        );
+        assert_eq!(
+            Launcher::new("firefox")
+                .display("https://a.b", &vars)
+                .unwrap(),
+            "firefox https://a.b"
+        );
+        assert!(Launcher {
+            args: vec![Template::new("<nope>")],
+            ..Launcher::new("vi")
+        }
+        .display("", &vars)
+        .is_err());
+
+        // Run from the crate root, where `src` and `Cargo.toml` exist
+        assert_eq!(Launcher::new("src").display("a", &vars).unwrap(), "src a");
+        let cargo = Path::new("Cargo.toml").canonicalize().unwrap();
+        assert_eq!(
+            Launcher::new("./Cargo.toml").display("a", &vars).unwrap(),
+            format!("{} a", cargo.display())
+        );
+    }
+}
@@ -0,0 +1,2219 @@
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+use crate::clipboard::Clipboard;
+use crate::config::{ordered, ConfigErrors, ConfigRegex};
+use crate::git::{Git, Remote, RemoteUrl};
+use crate::glob::Glob;
+use crate::open::history::{Entry, History};
+use crate::open::input::GitInput;
+use crate::open::launcher::{LaunchMode, Launcher};
+use crate::open::path::{path_vars, PathKind};
+use crate::open::template::{capture_vars, Template, Vars};
+use crate::open::when::{self as condition, When};
+use crate::picker::Picker;
+
+mod history;
+mod input;
+mod launcher;
+mod path;
+mod presets;
+mod template;
+mod when;
+
+pub use input::PR_TEXT;
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Open {
+    program: PathBuf,
+    patterns: Vec<PatternOpen>,
+    /// In the order they are written, which breaks ties between them
+    #[serde(with = "ordered")]
+    git: Vec<(String, GitOpen)>,
+    /// Built-in forges to add to `git`, see [`presets::PRESETS`]
+    providers: Vec<String>,
+    /// Named shortcuts, the text `name` opens the url. The templates can use
+    /// the variables of the remote url and `<branch>`.
+    aliases: BTreeMap<String, Template>,
+    /// Searches for texts starting with `keyword:`, e.g. `gh:query`
+    search: BTreeMap<String, SearchOpen>,
+    /// Url for a text nothing else matches, e.g. a web search with `<text|urlencode>`
+    fallback: Option<Template>,
+    /// Remotes to use for `git`, the first that exists is used
+    remotes: Vec<String>,
+    /// Remote given on the command line, overrides `remotes`
+    #[serde(skip)]
+    remote: Option<String>,
+    /// When to let the user pick between several candidates
+    choose: Choose,
+    /// Command used to pick a candidate, e.g. `["fzf"]`.
+    /// A numbered list is shown on the terminal if not set.
+    picker: Option<Vec<String>>,
+    /// Ask before opening more urls than this at once
+    confirm_above: usize,
+    /// Never open more urls than this at once
+    max_open: usize,
+    /// How to watch the launcher for failures
+    launch: LaunchMode,
+    /// Seconds to wait for the launcher with `launch = "wait"`
+    launch_timeout: u64,
+    /// Print the url instead of running `program` unless this holds
+    launch_when: Option<When>,
+    /// `osc8` prints hyperlinks instead of running any launcher.
+    /// If not set, `osc8` is used in an SSH session with stdout on a terminal.
+    launcher: Option<Emit>,
+    /// Given on the command line, overrides `launcher`
+    #[serde(skip)]
+    emit: Option<Emit>,
+    /// How many opened urls to remember for `--recent`, 0 remembers none
+    history_size: usize,
+    /// Unset on the command line to not record this invocation
+    #[serde(skip)]
+    history: bool,
+    clipboard: Clipboard,
+}
+
+impl Default for Open {
+    fn default() -> Self {
+        Self {
+            program: "xdg-open".into(),
+            patterns: Vec::new(),
+            git: Vec::new(),
+            providers: Vec::new(),
+            aliases: BTreeMap::new(),
+            search: BTreeMap::new(),
+            fallback: None,
+            remotes: Vec::new(),
+            remote: None,
+            choose: Choose::default(),
+            picker: None,
+            confirm_above: 5,
+            max_open: 20,
+            launch: LaunchMode::default(),
+            launch_timeout: 10,
+            launch_when: None,
+            launcher: None,
+            emit: None,
+            history_size: 1000,
+            history: true,
+            clipboard: Clipboard::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Choose {
+    /// Always ask when there is more than one candidate
+    Always,
+    /// Ask when the first candidates tie: same priority, both from git sections
+    /// or both plain patterns, and matching as much of the text
+    #[default]
+    Tie,
+    /// Never ask, take the first candidate
+    First,
+}
+
+/// Which part of the text a pattern has to match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MatchMode {
+    /// All of it
+    Full,
+    /// The start of it
+    Prefix,
+    /// Anywhere, unless the pattern is anchored itself
+    #[default]
+    Search,
+}
+
+/// How many urls `--recent` lists on the terminal.
+const RECENT_SHOWN: usize = 20;
+
+/// What to do with the url a text resolves to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Emit {
+    /// Run the launcher of the rule, or `program`
+    Launch,
+    /// Print an OSC 8 hyperlink to the url, labelled with the text
+    Osc8,
+}
+
+/// A search for `keyword:query`, `<query>` is the text after the keyword.
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchOpen {
+    url: Template,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PatternOpen {
+    #[serde(default)]
+    priority: i32,
+    pattern: ConfigRegex,
+    #[serde(default, rename = "match")]
+    match_mode: MatchMode,
+    url: Template,
+    /// Only match texts that are an existing path of this kind
+    kind: Option<PathKind>,
+    /// Only match texts that are an existing path with one of these extensions
+    #[serde(default)]
+    extension: Vec<String>,
+    /// Opens the url instead of `program`
+    launcher: Option<Launcher>,
+    /// Only use the pattern when this holds
+    when: Option<When>,
+    /// Only use the pattern in these directories of the repository, globs relative to its root
+    #[serde(default)]
+    paths: Vec<Glob>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GitOpen {
+    #[serde(default)]
+    priority: i32,
+    /// Use the settings of a built-in forge for every field not set
+    preset: Option<String>,
+    /// Host of the remote to match, if `remote` is not set.
+    /// Also replaces the default host of the preset.
+    host: Option<String>,
+    /// Regex matched against the remote url
+    remote: Option<ConfigRegex>,
+    url: Option<Template>,
+    branch: Option<Template>,
+    commit: Option<Template>,
+    /// Url for a tag, `commit` is used if not set
+    tag: Option<Template>,
+    /// Url comparing `<base>` to `<head>` for a range, `<dots>` is `..` or `...`
+    compare: Option<Template>,
+    /// Url for a file in the repository
+    file: Option<Template>,
+    /// Url for a line range in a file, `file` is used if not set
+    file_lines: Option<Template>,
+    /// Url for the pull request of the current branch, when its number `<pr>` is known
+    pr: Option<Template>,
+    /// Url to create a pull request for the current branch
+    new_pr: Option<Template>,
+    #[serde(default)]
+    patterns: Vec<PatternOpen>,
+    /// Opens the urls of this section instead of `program`,
+    /// the `launcher` of a pattern takes precedence
+    launcher: Option<Launcher>,
+    /// Only use the section when this holds
+    when: Option<When>,
+    /// Only use the section in these directories of the repository, globs relative to its root
+    #[serde(default)]
+    paths: Vec<Glob>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, Deserialize)]
+struct GitPrOpen {
+    pattern: String,
+    url: String,
+}
+
+/// What a [`GitOpen`] resolves an input to.
+#[derive(Debug, PartialEq, Eq)]
+struct GitBase<'a> {
+    /// The field that produced the url
+    field: String,
+    url: String,
+    /// Length of the text matched by a pattern, `usize::MAX` for the other fields
+    matched: usize,
+    /// The variables the url was rendered with
+    vars: Vars,
+    launcher: Option<&'a Launcher>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct CanOpen<'a> {
+    priority: i32,
+    url: String,
+    /// The text the url was resolved from
+    text: String,
+    /// Config path of the rule that produced the url
+    rule: String,
+    /// From a git section rather than a plain pattern
+    git: bool,
+    /// Length of the text the rule matched
+    matched: usize,
+    /// The variables the url was rendered with, used by the launcher
+    vars: Vars,
+    /// `program` is used if not set
+    launcher: Option<&'a Launcher>,
+}
+
+impl Open {
+    /// Open the url of every text, asking first if there are many.
+    pub fn open(&self, texts: &[String]) -> Result<()> {
+        let (can, failed) = self.resolve_texts(texts, true)?;
+        self.launch(can)?;
+        unresolved(failed)
+    }
+
+    /// Copy the url of every text to the clipboard, one per line,
+    /// and open them as well if `launch` is set.
+    pub fn copy(&self, texts: &[String], launch: bool) -> Result<()> {
+        let (can, failed) = self.resolve_texts(texts, false)?;
+
+        let urls = can.iter().map(|c| c.url.as_str()).collect::<Vec<_>>();
+        self.clipboard.copy(&urls.join("\n"))?;
+        info!("Copied {}", urls.join(" "));
+
+        if launch {
+            self.launch(can)?;
+        }
+
+        unresolved(failed)
+    }
+
+    /// The non-empty lines on the clipboard.
+    pub fn paste(&self) -> Result<Vec<String>> {
+        let texts = self
+            .clipboard
+            .paste()?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        if texts.is_empty() {
+            return Err(anyhow!("The clipboard is empty"));
+        }
+
+        Ok(texts)
+    }
+
+    fn launch(&self, can: Vec<CanOpen<'_>>) -> Result<()> {
+        if can.len() > self.max_open {
+            return Err(anyhow!(
+                "Refusing to open {} urls, the limit is {} (`max_open`)",
+                can.len(),
+                self.max_open
+            ));
+        }
+
+        if can.len() > self.confirm_above && !Picker::confirm(&format!("Open {} urls?", can.len()))?
+        {
+            return Err(anyhow!("Nothing opened"));
+        }
+
+        if let [first] = can.as_slice() {
+            self.launch_one(first)?;
+            self.record(&[first]);
+            return Ok(());
+        }
+
+        let mut opened = Vec::new();
+        for first in &can {
+            match self.launch_one(first) {
+                Ok(()) => opened.push(first),
+                Err(e) => error!("{e}"),
+            }
+        }
+        self.record(&opened);
+
+        let failed = can.len() - opened.len();
+
+        match failed {
+            0 => Ok(()),
+            _ => Err(anyhow!("Could not open {failed} of {} urls", can.len())),
+        }
+    }
+
+    /// Add the opened candidates to the history, failing to is only logged.
+    fn record(&self, opened: &[&CanOpen]) {
+        if !self.history || self.history_size == 0 || opened.is_empty() {
+            return;
+        }
+
+        let record = || -> Result<()> {
+            let path = History::path()?;
+            let mut history = History::load(&path)?;
+            let repo = Git::toplevel()?.map(|repo| repo.to_string_lossy().into_owned());
+            let time = history::now();
+
+            let entries = opened.iter().map(|can| Entry {
+                text: can.text.clone(),
+                url: can.url.clone(),
+                rule: can.rule.clone(),
+                repo: repo.clone(),
+                time,
+            });
+            history.push(entries, self.history_size);
+            history.save(&path)
+        };
+
+        if let Err(e) = record() {
+            warn!("Could not record the history: {e}");
+        }
+    }
+
+    /// The urls opened before, the most frecent first. Opens the one the user
+    /// picks, or only lists them with `print` or when the user cannot pick.
+    pub fn recent(&self, print: bool) -> Result<()> {
+        let history = History::load(&History::path()?)?;
+        let recent = history.recent(history::now());
+        if recent.is_empty() {
+            return Err(anyhow!("Nothing was opened yet"));
+        }
+
+        if print || !Picker::is_interactive() {
+            for r in &recent {
+                println!("{}\t{}\t{}", r.entry.url, r.entry.text, r.entry.rule);
+            }
+            return Ok(());
+        }
+
+        // A numbered list on the terminal has to stay short
+        let shown = match self.picker {
+            Some(_) => recent.len(),
+            None => recent.len().min(RECENT_SHOWN),
+        };
+        let items = recent[..shown]
+            .iter()
+            .map(|r| format!("{}\t{}", r.entry.url, r.entry.text))
+            .collect::<Vec<_>>();
+        let entry = recent[Picker::pick(&items, self.picker.as_deref())?].entry;
+
+        self.launch(vec![CanOpen {
+            priority: 0,
+            url: entry.url.clone(),
+            text: entry.text.clone(),
+            rule: entry.rule.clone(),
+            git: false,
+            matched: 0,
+            vars: Vars::new(),
+            launcher: None,
+        }])
+    }
+
+    /// Print the url every text resolves to instead of opening it.
+    /// With `all`, print every candidate together with its priority and rule.
+    pub fn print(&self, texts: &[String], all: bool) -> Result<()> {
+        self.print_to(&mut io::stdout().lock(), texts, all)
+    }
+
+    fn print_to(&self, out: &mut impl Write, texts: &[String], all: bool) -> Result<()> {
+        if !all {
+            let (can, failed) = self.resolve_texts(texts, false)?;
+            for first in can {
+                writeln!(out, "{}", first.url)?;
+            }
+            return unresolved(failed);
+        }
+
+        let failed = each_text(texts, |text| {
+            for CanOpen {
+                priority,
+                url,
+                rule,
+                ..
+            } in self.resolve_some(text)?
+            {
+                writeln!(out, "{text}\t{priority}\t{rule}\t{url}")?;
+            }
+            Ok(())
+        })?;
+
+        unresolved(failed)
+    }
+
+    /// The candidate to open for each text, without duplicate urls.
+    /// With several texts, the ones that do not resolve are reported, skipped
+    /// and counted. The user picks between candidates only with `pick`.
+    fn resolve_texts(&self, texts: &[String], pick: bool) -> Result<(Vec<CanOpen<'_>>, usize)> {
+        let mut can: Vec<CanOpen> = Vec::new();
+        let failed = each_text(texts, |text| {
+            let first = self.resolve_one(text, pick)?;
+            if !can.iter().any(|c| c.url == first.url) {
+                can.push(first);
+            }
+            Ok(())
+        })?;
+
+        if can.is_empty() {
+            return Err(anyhow!("Nothing to open"));
+        }
+
+        Ok((can, failed))
+    }
+
+    /// Every part of the texts that a pattern matches and that the same pattern,
+    /// with its conditions, turns into a url. In order of appearance and without
+    /// duplicates.
+    ///
+    /// Patterns that accept nothing they find in a text, e.g. anchored ones like
+    /// `^(\d+)$`, and patterns for paths are matched against each word instead.
+    pub fn extract(&self, texts: &[String]) -> Result<Vec<String>> {
+        let remote = if self.git.is_empty() {
+            None
+        } else {
+            self.current_remote()?
+        };
+
+        // The patterns of git sections render with the variables of the remote
+        let mut patterns = self
+            .patterns
+            .iter()
+            .map(|p| (p, Vars::new()))
+            .collect::<Vec<_>>();
+        for (_, git_open) in &self.git {
+            if !condition::holds(&git_open.when) || !condition::in_paths(&git_open.paths) {
+                continue;
+            }
+            let Some(vars) = remote.as_ref().and_then(|r| git_open.remote_vars(&r.url)) else {
+                continue;
+            };
+            patterns.extend(git_open.patterns.iter().map(|p| (p, vars.clone())));
+        }
+        let accepts = |pattern: &PatternOpen, vars: &Vars, token: &str| {
+            matches!(pattern.get_match(token, vars), Ok(Some(_)))
+        };
+
+        let mut found = Vec::new();
+        for text in texts {
+            let mut matches = Vec::new();
+            for (pattern, vars) in &patterns {
+                // A path is a whole word, not any part of the text a pattern finds
+                if !pattern.is_path() {
+                    let len = matches.len();
+                    for m in pattern.pattern.find_iter(text) {
+                        if accepts(pattern, vars, m.as_str()) {
+                            matches.push((m.start(), m.as_str()));
+                        }
+                    }
+                    if matches.len() > len {
+                        continue;
+                    }
+                }
+
+                for (start, word) in words(text) {
+                    if accepts(pattern, vars, word) {
+                        matches.push((start, word));
+                    }
+                }
+            }
+
+            matches.sort();
+            found.extend(matches.into_iter().map(|(_, m)| m.to_string()));
+        }
+
+        let mut extracted: Vec<String> = Vec::new();
+        for text in found {
+            if !extracted.contains(&text) {
+                extracted.push(text);
+            }
+        }
+
+        Ok(extracted)
+    }
+
+    /// All candidates for `text`, the one that would be opened first.
+    ///
+    /// An alias is the only candidate for its name and so is a search for
+    /// `keyword:query`. Otherwise the highest priority comes first. Ties go
+    /// to git sections before plain patterns, then to the rule that matched
+    /// more of the text and finally to the rule written first.
+    fn resolve(&self, text: &str) -> Result<Vec<CanOpen<'_>>> {
+        if let Some(alias) = self.open_alias(text)? {
+            return Ok(vec![alias]);
+        }
+        if let Some(search) = self.open_search(text)? {
+            return Ok(vec![search]);
+        }
+
+        let mut can = Vec::new();
+
+        can.append(&mut self.open_git(text)?);
+        can.append(&mut self.open_pattern(text)?);
+        sort_candidates(&mut can);
+
+        if can.is_empty() {
+            can.extend(self.open_fallback(text)?);
+        }
+
+        Ok(can)
+    }
+
+    /// The candidates for `text`, an error if there are none.
+    fn resolve_some(&self, text: &str) -> Result<Vec<CanOpen<'_>>> {
+        let can = self.resolve(text)?;
+        if can.is_empty() {
+            return Err(anyhow!("Could not find pattern for {text}"));
+        }
+
+        Ok(can)
+    }
+
+    /// The candidate to open for `text`, asking the user if configured to and `pick` is set.
+    fn resolve_one(&self, text: &str, pick: bool) -> Result<CanOpen<'_>> {
+        let mut can = self.resolve_some(text)?;
+
+        let choices = self.choices(&can);
+        if pick && choices > 1 && Picker::is_interactive() {
+            let items = can[..choices]
+                .iter()
+                .map(|c| format!("{}\t{}", c.url, c.rule))
+                .collect::<Vec<_>>();
+            let picked = Picker::pick(&items, self.picker.as_deref())?;
+            return Ok(can.swap_remove(picked));
+        }
+
+        Ok(can.swap_remove(0))
+    }
+
+    /// How many of the sorted candidates the user should pick from.
+    fn choices(&self, can: &[CanOpen]) -> usize {
+        match self.choose {
+            Choose::Always => can.len(),
+            Choose::Tie => can
+                .iter()
+                .take_while(|c| sort_key(c) == sort_key(&can[0]))
+                .count(),
+            Choose::First => can.len().min(1),
+        }
+    }
+
+    fn open_pattern(&self, text: &str) -> Result<Vec<CanOpen<'_>>> {
+        let mut can = Vec::new();
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            if let Some((url, vars)) = pattern.get_match(text, &Vars::new())? {
+                can.push(CanOpen {
+                    priority: pattern.priority,
+                    url,
+                    text: text.to_string(),
+                    rule: format!("open.patterns[{i}]"),
+                    git: false,
+                    matched: pattern.matched(text),
+                    vars,
+                    launcher: pattern.launcher.as_ref(),
+                });
+            }
+        }
+
+        Ok(can)
+    }
+
+    /// The url of the alias named `text`.
+    fn open_alias(&self, text: &str) -> Result<Option<CanOpen<'_>>> {
+        let Some(template) = self.aliases.get(text) else {
+            return Ok(None);
+        };
+
+        let vars = self.alias_vars()?;
+        Ok(Some(CanOpen {
+            priority: 0,
+            url: template.render(&vars)?,
+            text: text.to_string(),
+            rule: format!("open.aliases.{text}"),
+            git: false,
+            matched: usize::MAX,
+            vars,
+            launcher: None,
+        }))
+    }
+
+    /// The search url for `keyword:query` if there is a search for `keyword`.
+    fn open_search(&self, text: &str) -> Result<Option<CanOpen<'_>>> {
+        let Some((keyword, search, query)) = self.find_search(text) else {
+            return Ok(None);
+        };
+
+        let rule = format!("open.search.{keyword}");
+        search_candidate(rule, &search.url, text, query).map(Some)
+    }
+
+    /// The keyword, search and trimmed query of `keyword:query`,
+    /// if there is a search for `keyword` and the query is not empty.
+    fn find_search<'t>(&self, text: &'t str) -> Option<(&'t str, &SearchOpen, &'t str)> {
+        let (keyword, query) = text.split_once(':')?;
+        let query = query.trim();
+        let search = self.search.get(keyword).filter(|_| !query.is_empty())?;
+        Some((keyword, search, query))
+    }
+
+    /// The `fallback` url for a text nothing else matched.
+    fn open_fallback(&self, text: &str) -> Result<Option<CanOpen<'_>>> {
+        let Some(fallback) = &self.fallback else {
+            return Ok(None);
+        };
+
+        search_candidate("open.fallback".into(), fallback, text, text).map(Some)
+    }
+
+    /// The variables of the remote url and `<branch>`, those that are known.
+    fn alias_vars(&self) -> Result<Vars> {
+        let mut vars = self
+            .current_remote()?
+            .and_then(|remote| RemoteUrl::parse(&remote.url))
+            .map(|url| remote_url_vars(&url))
+            .unwrap_or_default();
+
+        if let Some(branch) = Git::get_branch()? {
+            vars.insert("branch".into(), branch);
+        }
+
+        Ok(vars)
+    }
+
+    /// Print every alias with its url, or its template if it cannot be rendered here.
+    pub fn list_aliases(&self) -> Result<()> {
+        let vars = self.alias_vars()?;
+        for (name, template) in &self.aliases {
+            match template.render(&vars) {
+                Ok(url) => println!("{name}\t{url}"),
+                Err(_) => println!("{name}\t{template}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn open_git(&self, text: &str) -> Result<Vec<CanOpen<'_>>> {
+        let input = GitInput::classify(text);
+
+        match self.current_remote()? {
+            Some(remote) => self.git_candidates(text, &input, &remote.url),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The candidates of every git section matching `remote`, in config order.
+    fn git_candidates(
+        &self,
+        text: &str,
+        input: &GitInput,
+        remote: &str,
+    ) -> Result<Vec<CanOpen<'_>>> {
+        let mut can = Vec::new();
+
+        for (name, git_open) in &self.git {
+            if !condition::holds(&git_open.when) || !condition::in_paths(&git_open.paths) {
+                continue;
+            }
+            let Some(vars) = git_open.remote_vars(remote) else {
+                continue;
+            };
+
+            if let Some(base) = git_open.get_base(input, &vars)? {
+                can.push(CanOpen {
+                    priority: git_open.priority,
+                    url: base.url,
+                    text: text.to_string(),
+                    rule: format!("open.git.{name}.{}", base.field),
+                    git: true,
+                    matched: base.matched,
+                    vars: base.vars,
+                    launcher: base.launcher,
+                });
+            }
+        }
+
+        Ok(can)
+    }
+
+    /// Use the remote `name` for `git` instead of the configured `remotes`.
+    pub fn set_remote(&mut self, name: Option<String>) {
+        self.remote = name;
+    }
+
+    /// Whether to add what is opened to the history.
+    pub fn set_history(&mut self, record: bool) {
+        self.history = record;
+    }
+
+    /// Handle the urls as `emit` says instead of the configured `launcher`.
+    pub fn set_emit(&mut self, emit: Option<Emit>) {
+        self.emit = emit;
+    }
+
+    fn emit(&self) -> Emit {
+        self.emit.or(self.launcher).unwrap_or_else(|| {
+            if env::var_os("SSH_CONNECTION").is_some() && io::stdout().is_terminal() {
+                Emit::Osc8
+            } else {
+                Emit::Launch
+            }
+        })
+    }
+
+    fn get_remote(&self) -> Result<Option<Remote>> {
+        Git::get_remote(self.remote.as_deref(), &self.remotes)
+    }
+
+    /// The remote to resolve texts with. Failing to find one only logs the
+    /// error, unless the remote was asked for on the command line.
+    fn current_remote(&self) -> Result<Option<Remote>> {
+        match self.get_remote() {
+            Ok(remote) => Ok(remote),
+            // A remote asked for on the command line must exist
+            Err(e) if self.remote.is_some() => Err(e),
+            Err(e) => {
+                error!("{e}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Add the sections for `providers` and fill every section from its preset.
+    pub fn apply_presets(&mut self, errors: &mut ConfigErrors) {
+        for provider in &self.providers {
+            if !self.git.iter().any(|(name, _)| name == provider) {
+                let git_open = GitOpen {
+                    preset: Some(provider.clone()),
+                    ..Default::default()
+                };
+                self.git.push((provider.clone(), git_open));
+            }
+        }
+
+        for (name, git_open) in &mut self.git {
+            let Some(preset) = &git_open.preset else {
+                continue;
+            };
+
+            match presets::preset(preset, git_open.host.as_deref()) {
+                Some(preset) => git_open.fill_from(preset),
+                None => errors.push_missing(
+                    &format!("open.git.{name}.preset"),
+                    format!(
+                        "Unknown preset `{preset}`, expected one of: {}",
+                        presets::PRESETS.join(", ")
+                    ),
+                ),
+            }
+        }
+    }
+
+    /// Anchor every pattern as its `match` says.
+    pub fn apply_match_modes(&mut self) {
+        let git_patterns = self.git.iter_mut().flat_map(|(_, g)| &mut g.patterns);
+        for pattern in self.patterns.iter_mut().chain(git_patterns) {
+            pattern.pattern = match pattern.match_mode {
+                MatchMode::Full => pattern.pattern.anchored(true, true),
+                MatchMode::Prefix => pattern.pattern.anchored(true, false),
+                MatchMode::Search => continue,
+            };
+        }
+    }
+
+    /// Report every invalid regex and template in the config.
+    pub fn validate(&self, errors: &mut ConfigErrors) {
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            pattern.validate(&format!("open.patterns[{i}]"), errors);
+        }
+
+        for (name, git_open) in &self.git {
+            git_open.validate(&format!("open.git.{name}"), errors);
+        }
+
+        for (name, template) in &self.aliases {
+            template.validate(&format!("open.aliases.{name}"), errors);
+        }
+        for (keyword, search) in &self.search {
+            search
+                .url
+                .validate(&format!("open.search.{keyword}.url"), errors);
+        }
+        if let Some(fallback) = &self.fallback {
+            fallback.validate("open.fallback", errors);
+        }
+
+        if let Some(when) = &self.launch_when {
+            when.validate("open.launch_when", errors);
+        }
+    }
+
+    /// Print how every rule reacts to each text and the final ordering.
+    pub fn explain(&self, texts: &[String]) -> Result<()> {
+        self.explain_to(&mut io::stdout().lock(), texts)
+    }
+
+    fn explain_to(&self, out: &mut impl Write, texts: &[String]) -> Result<()> {
+        for (i, text) in texts.iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            self.explain_text(out, text)?;
+        }
+
+        Ok(())
+    }
+
+    fn explain_text(&self, out: &mut impl Write, text: &str) -> Result<()> {
+        writeln!(out, "Input: {text}")?;
+
+        let input = GitInput::classify(text);
+        writeln!(out, "Git: {input}")?;
+
+        let remote = match self.get_remote() {
+            Ok(remote) => remote,
+            Err(e) => {
+                error!("{e}");
+                None
+            }
+        };
+        match &remote {
+            Some(remote) => writeln!(out, "Remote: {} {}", remote.name, remote.url)?,
+            None => writeln!(out, "Remote: none")?,
+        }
+        writeln!(out)?;
+
+        if let Some(template) = self.aliases.get(text) {
+            writeln!(
+                out,
+                "open.aliases.{text} `{template}`: matched, no other rule applies"
+            )?;
+        }
+        if let Some((keyword, search, _)) = self.find_search(text) {
+            writeln!(
+                out,
+                "open.search.{keyword} `{}`: matched, no other rule applies",
+                search.url
+            )?;
+        }
+
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            pattern.explain(out, &format!("open.patterns[{i}]"), text, "")?;
+        }
+
+        for (name, git_open) in &self.git {
+            let rule = format!("open.git.{name}");
+            let vars = remote
+                .as_ref()
+                .and_then(|remote| git_open.remote_vars(&remote.url));
+
+            let matcher = match (&git_open.remote, &git_open.host) {
+                (Some(remote), _) => format!("remote `{remote}`"),
+                (None, Some(host)) => format!("host `{host}`"),
+                (None, None) => "no remote".to_string(),
+            };
+            if !condition::holds(&git_open.when) {
+                writeln!(out, "{rule}: skipped, `when` does not hold")?;
+                continue;
+            }
+            if !condition::in_paths(&git_open.paths) {
+                writeln!(
+                    out,
+                    "{rule}: skipped, not in {}",
+                    format_paths(&git_open.paths)
+                )?;
+                continue;
+            }
+            let Some(vars) = vars else {
+                writeln!(out, "{rule} {matcher}: no match")?;
+                continue;
+            };
+            writeln!(out, "{rule} {matcher}: matched {}", format_vars(&vars))?;
+
+            if let GitInput::Text(text) = &input {
+                for (i, pattern) in git_open.patterns.iter().enumerate() {
+                    pattern.explain(out, &format!("{rule}.patterns[{i}]"), text, "  ")?;
+                }
+            }
+
+            match git_open.get_base(&input, &vars) {
+                Ok(Some(base)) => writeln!(out, "  {rule}: uses `{}`", base.field)?,
+                Ok(None) => writeln!(out, "  {rule}: no template applies")?,
+                Err(e) => writeln!(out, "  {rule}: {e}")?,
+            }
+        }
+
+        writeln!(out)?;
+        writeln!(out, "Candidates (first is opened):")?;
+        let can = self.resolve(text)?;
+        if can.is_empty() {
+            writeln!(out, "  none")?;
+        }
+        for (i, can) in can.iter().enumerate() {
+            writeln!(
+                out,
+                "  {}. priority {} {} {}",
+                i + 1,
+                can.priority,
+                can.rule,
+                can.url
+            )?;
+            if self.emit() == Emit::Osc8 {
+                writeln!(out, "     printed as an OSC 8 hyperlink")?;
+            } else if let Some(launcher) = can.launcher {
+                match launcher.display(&can.url, &can.vars) {
+                    Ok(command) => writeln!(out, "     runs: {command}")?,
+                    Err(e) => writeln!(out, "     runs: {e}")?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn launch_one(&self, can: &CanOpen) -> Result<()> {
+        info!("Opening: {}", can.url);
+
+        if self.emit() == Emit::Osc8 {
+            println!("{}", osc8(&can.url, &can.text));
+            return Ok(());
+        }
+
+        if let Some(launcher) = can.launcher.filter(|l| l.applies()) {
+            return launcher.launch(&can.url, &can.vars, self.launch, self.launch_timeout);
+        }
+
+        if !condition::holds(&self.launch_when) {
+            println!("{}", can.url);
+            return Ok(());
+        }
+
+        Launcher::new(&self.program.to_string_lossy()).launch(
+            &can.url,
+            &can.vars,
+            self.launch,
+            self.launch_timeout,
+        )
+    }
+}
+
+impl GitOpen {
+    /// Take every field that is not set from `preset`.
+    fn fill_from(&mut self, preset: GitOpen) {
+        self.host = self.host.take().or(preset.host);
+        self.remote = self.remote.take().or(preset.remote);
+        self.url = self.url.take().or(preset.url);
+        self.branch = self.branch.take().or(preset.branch);
+        self.commit = self.commit.take().or(preset.commit);
+        self.tag = self.tag.take().or(preset.tag);
+        self.compare = self.compare.take().or(preset.compare);
+        self.file = self.file.take().or(preset.file);
+        self.file_lines = self.file_lines.take().or(preset.file_lines);
+        self.pr = self.pr.take().or(preset.pr);
+        self.new_pr = self.new_pr.take().or(preset.new_pr);
+        if self.patterns.is_empty() {
+            self.patterns = preset.patterns;
+        }
+        self.launcher = self.launcher.take().or(preset.launcher);
+    }
+
+    fn validate(&self, path: &str, errors: &mut ConfigErrors) {
+        match &self.remote {
+            Some(remote) => remote.validate(&format!("{path}.remote"), errors),
+            None if self.host.is_none() => {
+                errors.push_missing(path, "missing `remote` or `host` (or `preset`)");
+            }
+            None => {}
+        }
+        match &self.url {
+            Some(url) => url.validate(&format!("{path}.url"), errors),
+            None => errors.push_missing(path, "missing `url` (or `preset`)"),
+        }
+        if let Some(branch) = &self.branch {
+            branch.validate(&format!("{path}.branch"), errors);
+        }
+        if let Some(commit) = &self.commit {
+            commit.validate(&format!("{path}.commit"), errors);
+        }
+        if let Some(tag) = &self.tag {
+            tag.validate(&format!("{path}.tag"), errors);
+        }
+        if let Some(compare) = &self.compare {
+            compare.validate(&format!("{path}.compare"), errors);
+        }
+        if let Some(file) = &self.file {
+            file.validate(&format!("{path}.file"), errors);
+        }
+        if let Some(file_lines) = &self.file_lines {
+            file_lines.validate(&format!("{path}.file_lines"), errors);
+        }
+        if let Some(pr) = &self.pr {
+            pr.validate(&format!("{path}.pr"), errors);
+        }
+        if let Some(new_pr) = &self.new_pr {
+            new_pr.validate(&format!("{path}.new_pr"), errors);
+        }
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            pattern.validate(&format!("{path}.patterns[{i}]"), errors);
+        }
+        if let Some(launcher) = &self.launcher {
+            launcher.validate(&format!("{path}.launcher"), errors);
+        }
+        if let Some(when) = &self.when {
+            when.validate(&format!("{path}.when"), errors);
+        }
+    }
+
+    /// The variables for `remote` if it matches: `<host>`, `<port>`,
+    /// `<owner>`, `<subgroups>`, `<namespace>` and `<repo>` from the parsed url
+    /// and, with a `remote` regex, `<rN>` and its named groups.
+    fn remote_vars(&self, remote: &str) -> Option<Vars> {
+        let url = RemoteUrl::parse(remote);
+
+        let mut vars = match (&self.remote, &self.host) {
+            (Some(regex), _) => capture_vars("r", regex, remote)?,
+            (None, Some(host)) => {
+                let remote_host = url.as_ref()?.host.as_deref()?;
+                if !remote_host.eq_ignore_ascii_case(host) {
+                    return None;
+                }
+                Vars::new()
+            }
+            (None, None) => return None,
+        };
+
+        if let Some(url) = url {
+            for (name, value) in remote_url_vars(&url) {
+                vars.entry(name).or_insert(value);
+            }
+        }
+
+        Some(vars)
+    }
+
+    /// The url for `input` together with the field that produced it.
+    fn get_base(&self, input: &GitInput, vars: &Vars) -> Result<Option<GitBase<'_>>> {
+        let mut vars = vars.clone();
+        let base = |field: &str, template: &Template, vars: Vars| {
+            Ok(Some(GitBase {
+                field: field.into(),
+                url: template.render(&vars)?,
+                // The input as a whole
+                matched: usize::MAX,
+                vars,
+                launcher: self.launcher.as_ref(),
+            }))
+        };
+
+        match input {
+            GitInput::Repo { branch } => {
+                if let (Some(branch_url), Some(branch)) = (&self.branch, branch) {
+                    vars.insert("branch".into(), branch.clone());
+                    return base("branch", branch_url, vars);
+                }
+
+                let Some(url) = &self.url else {
+                    return Ok(None);
+                };
+
+                base("url", url, vars)
+            }
+            GitInput::Branch(_) | GitInput::Tag { .. } => {
+                let (field, named) = match input {
+                    GitInput::Branch(_) => ("branch", &self.branch),
+                    _ => ("tag", &self.tag),
+                };
+                let (field, template) = match (named, &self.commit) {
+                    (Some(named), _) => (field, named),
+                    (None, Some(commit)) => ("commit", commit),
+                    _ => return Ok(None),
+                };
+
+                vars.append(&mut input.vars());
+                base(field, template, vars)
+            }
+            GitInput::Commit(commit) => {
+                let Some(commit_url) = &self.commit else {
+                    return Ok(None);
+                };
+
+                vars.insert("commit".into(), commit.clone());
+                base("commit", commit_url, vars)
+            }
+            GitInput::Range(_) => {
+                let Some(compare) = &self.compare else {
+                    return Ok(None);
+                };
+
+                vars.append(&mut input.vars());
+                base("compare", compare, vars)
+            }
+            GitInput::File(file) => {
+                let (field, template) = match (&self.file_lines, &self.file) {
+                    (Some(lines), _) if file.lines.is_some() => ("file_lines", lines),
+                    (_, Some(file)) => ("file", file),
+                    _ => return Ok(None),
+                };
+
+                vars.append(&mut file.vars());
+                base(field, template, vars)
+            }
+            GitInput::PullRequest(pr) => {
+                let (field, template) = match (&self.pr, &self.new_pr) {
+                    (Some(known), _) if pr.number.is_some() => ("pr", known),
+                    (_, Some(new_pr)) => ("new_pr", new_pr),
+                    _ => return Ok(None),
+                };
+
+                vars.append(&mut pr.vars());
+                base(field, template, vars)
+            }
+            GitInput::Text(text) => {
+                for (i, pattern) in self.patterns.iter().enumerate() {
+                    if let Some((url, vars)) = pattern.get_match(text, &vars)? {
+                        return Ok(Some(GitBase {
+                            field: format!("patterns[{i}]"),
+                            url,
+                            matched: pattern.matched(text),
+                            vars,
+                            launcher: pattern.launcher.as_ref().or(self.launcher.as_ref()),
+                        }));
+                    }
+                }
+
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl PatternOpen {
+    /// Open `url` for texts that `pattern` finds.
+    fn new(pattern: &str, url: &str) -> Self {
+        Self {
+            priority: 0,
+            pattern: ConfigRegex::new(pattern),
+            match_mode: MatchMode::Search,
+            url: Template::new(url),
+            kind: None,
+            extension: Vec::new(),
+            launcher: None,
+            when: None,
+            paths: Vec::new(),
+        }
+    }
+
+    /// The url and the variables it was rendered with if the pattern matches `text`.
+    /// The template can use `vars` as well as the groups of the pattern.
+    fn get_match(&self, text: &str, vars: &Vars) -> Result<Option<(String, Vars)>> {
+        let Some(mut groups) = self.vars(text) else {
+            return Ok(None);
+        };
+
+        let mut vars = vars.clone();
+        vars.append(&mut groups);
+        let url = self.url.render(&vars)?;
+        Ok(Some((url, vars)))
+    }
+
+    /// Whether the pattern only matches existing paths.
+    fn is_path(&self) -> bool {
+        self.kind.is_some() || !self.extension.is_empty()
+    }
+
+    /// The length of the part of `text` the pattern matches.
+    fn matched(&self, text: &str) -> usize {
+        self.pattern.find(text).map_or(0, |m| m.len())
+    }
+
+    /// The variables bound by matching `text`, `<patN>` and named groups,
+    /// and the path variables if the pattern has path conditions.
+    fn vars(&self, text: &str) -> Option<Vars> {
+        if !condition::holds(&self.when) || !condition::in_paths(&self.paths) {
+            return None;
+        }
+
+        let mut vars = capture_vars("pat", &self.pattern, text)?;
+        if self.is_path() {
+            vars.append(&mut path_vars(text, self.kind, &self.extension)?);
+        }
+
+        Some(vars)
+    }
+
+    fn validate(&self, path: &str, errors: &mut ConfigErrors) {
+        self.pattern.validate(&format!("{path}.pattern"), errors);
+        self.url.validate(&format!("{path}.url"), errors);
+        if let Some(launcher) = &self.launcher {
+            launcher.validate(&format!("{path}.launcher"), errors);
+        }
+        if let Some(when) = &self.when {
+            when.validate(&format!("{path}.when"), errors);
+        }
+    }
+
+    fn explain(&self, out: &mut impl Write, rule: &str, text: &str, indent: &str) -> Result<()> {
+        if !condition::holds(&self.when) {
+            writeln!(out, "{indent}{rule}: skipped, `when` does not hold")?;
+            return Ok(());
+        }
+        if !condition::in_paths(&self.paths) {
+            writeln!(
+                out,
+                "{indent}{rule}: skipped, not in {}",
+                format_paths(&self.paths)
+            )?;
+            return Ok(());
+        }
+
+        match self.vars(text) {
+            Some(vars) => writeln!(
+                out,
+                "{indent}{rule} `{}`: matched {}",
+                self.pattern,
+                format_vars(&vars)
+            ),
+            None if self.pattern.captures(text).is_some() => writeln!(
+                out,
+                "{indent}{rule} `{}`: matched, but not an existing {}{}",
+                self.pattern,
+                match self.kind {
+                    Some(PathKind::File) => "file",
+                    Some(PathKind::Dir) => "directory",
+                    _ => "path",
+                },
+                match self.extension.as_slice() {
+                    [] => String::new(),
+                    extensions => format!(" ending in {}", extensions.join(", ")),
+                }
+            ),
+            None => writeln!(out, "{indent}{rule} `{}`: no match", self.pattern),
+        }?;
+
+        Ok(())
+    }
+}
+
+/// A candidate searching for `query`, with `<text>` and `<query>` to render `url`.
+fn search_candidate<'a>(
+    rule: String,
+    url: &Template,
+    text: &str,
+    query: &str,
+) -> Result<CanOpen<'a>> {
+    let vars = Vars::from([
+        ("text".to_string(), text.to_string()),
+        ("query".to_string(), query.to_string()),
+    ]);
+
+    Ok(CanOpen {
+        priority: 0,
+        url: url.render(&vars)?,
+        text: text.to_string(),
+        rule,
+        git: false,
+        matched: 0,
+        vars,
+        launcher: None,
+    })
+}
+
+/// Call `f` for every text. With several texts, the ones it fails for are
+/// reported and skipped, and how many there were is returned.
+fn each_text(texts: &[String], mut f: impl FnMut(&str) -> Result<()>) -> Result<usize> {
+    let mut failed = 0;
+    for text in texts {
+        match f(text) {
+            Ok(()) => {}
+            Err(e) if texts.len() > 1 => {
+                error!("{e}");
+                failed += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(failed)
+}
+
+/// An error if `failed` texts of several could not be resolved,
+/// once the others were handled.
+fn unresolved(failed: usize) -> Result<()> {
+    match failed {
+        0 => Ok(()),
+        1 => Err(anyhow!("Could not resolve 1 text")),
+        n => Err(anyhow!("Could not resolve {n} texts")),
+    }
+}
+
+/// Sort candidates from [`Open::git_candidates`] followed by [`Open::open_pattern`],
+/// see [`Open::resolve`]. The sort is stable, so config order breaks the last ties.
+fn sort_candidates(can: &mut [CanOpen]) {
+    can.sort_by_key(sort_key);
+}
+
+/// What candidates are ordered by, equal keys tie.
+fn sort_key(can: &CanOpen) -> (Reverse<i32>, bool, Reverse<usize>) {
+    (Reverse(can.priority), !can.git, Reverse(can.matched))
+}
+
+/// The words of `text` with their position, without surrounding punctuation.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace().filter_map(move |word| {
+        let trimmed = word
+            .trim_start_matches(['(', '[', '{', '<', '"', '\'', '`'])
+            .trim_end_matches([
+                ')', ']', '}', '>', '"', '\'', '`', ',', ';', ':', '.', '?', '!',
+            ]);
+
+        let start = word.as_ptr() as usize - text.as_ptr() as usize;
+        let start = start + word.find(trimmed)?;
+
+        (!trimmed.is_empty()).then_some((start, trimmed))
+    })
+}
+
+fn remote_url_vars(url: &RemoteUrl) -> Vars {
+    let mut vars = Vars::new();
+    let mut insert = |name: &str, value: Option<&str>| {
+        if let Some(value) = value {
+            vars.insert(name.to_string(), value.to_string());
+        }
+    };
+
+    insert("host", url.host.as_deref());
+    insert("port", url.port.map(|p| p.to_string()).as_deref());
+    insert("owner", url.owner());
+    insert("subgroups", Some(&url.subgroups().join("/")));
+    insert("namespace", url.namespace());
+    insert("repo", url.repo());
+
+    vars
+}
+
+fn format_vars(vars: &Vars) -> String {
+    if vars.is_empty() {
+        return "(no groups)".to_string();
+    }
+
+    vars.iter()
+        .map(|(name, value)| format!("<{name}>={value}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An OSC 8 hyperlink to `url` showing `label`, without the control
+/// characters either could use to end the escape sequence early.
+fn osc8(url: &str, label: &str) -> String {
+    let clean = |s: &str| s.chars().filter(|c| !c.is_control()).collect::<String>();
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", clean(url), clean(label))
+}
+
+/// `paths` for explain, e.g. `services/a/**`, `web/**`.
+fn format_paths(paths: &[Glob]) -> String {
+    paths
+        .iter()
+        .map(|glob| format!("`{glob}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::open::input::{BranchPr, BranchRef, RepoFile, RevRange};
+
+    fn get_open_config() -> Open {
+        Open {
+            program: "echo".into(),
+            patterns: vec![PatternOpen {
+                url: Template::new("https://example.com/<pat1>"),
+                ..pattern(1, r"test-(\d+)", MatchMode::Search)
+            }],
+            git: Vec::new(),
+            providers: Vec::new(),
+            aliases: BTreeMap::new(),
+            search: BTreeMap::new(),
+            fallback: None,
+            remotes: Vec::new(),
+            remote: None,
+            choose: Choose::Tie,
+            picker: None,
+            confirm_above: 5,
+            max_open: 20,
+            launch: LaunchMode::Monitor,
+            launch_timeout: 10,
+            launch_when: None,
+            launcher: None,
+            emit: None,
+            history_size: 1000,
+            history: true,
+            clipboard: Clipboard::default(),
+        }
+    }
+
+    #[test]
+    fn test_choices() {
+        let can = |priority| CanOpen {
+            priority,
+            url: String::new(),
+            text: String::new(),
+            rule: String::new(),
+            git: false,
+            matched: 0,
+            vars: Vars::new(),
+            launcher: None,
+        };
+        let mut open = get_open_config();
+        let candidates = [can(2), can(2), can(1)];
+
+        assert_eq!(open.choices(&candidates), 2);
+        assert_eq!(open.choices(&candidates[1..]), 1);
+        let git = CanOpen {
+            git: true,
+            ..can(2)
+        };
+        assert_eq!(open.choices(&[git, can(2)]), 1);
+        let matched = CanOpen {
+            matched: 3,
+            ..can(2)
+        };
+        assert_eq!(open.choices(&[matched, can(2)]), 1);
+        open.choose = Choose::Always;
+        assert_eq!(open.choices(&candidates), 3);
+        open.choose = Choose::First;
+        assert_eq!(open.choices(&candidates), 1);
+        assert_eq!(open.choices(&[]), 0);
+    }
+
+    #[test]
+    fn test_open_alias() {
+        let mut open = get_open_config();
+        open.aliases = BTreeMap::from([
+            (
+                "docs".to_string(),
+                Template::new("https://wiki.example.com"),
+            ),
+            ("ci".to_string(), Template::new("https://ci/<nope>")),
+        ]);
+
+        let alias = open.open_alias("docs").unwrap().unwrap();
+        assert_eq!(alias.url, "https://wiki.example.com");
+        assert_eq!(alias.rule, "open.aliases.docs");
+        assert!(open.open_alias("wiki").unwrap().is_none());
+        assert!(open.open_alias("ci").is_err());
+    }
+
+    #[test]
+    fn test_search_and_fallback() {
+        let mut open = get_open_config();
+        open.search = BTreeMap::from([(
+            "gh".to_string(),
+            SearchOpen {
+                url: Template::new("https://github.com/search?q=<query|urlencode>"),
+            },
+        )]);
+        let url = |can: Option<CanOpen>| can.map(|c| (c.rule, c.url));
+
+        assert_eq!(
+            url(open.open_search("gh: open url").unwrap()),
+            Some((
+                "open.search.gh".to_string(),
+                "https://github.com/search?q=open%20url".to_string()
+            ))
+        );
+        assert_eq!(url(open.open_search("gh:").unwrap()), None);
+        assert_eq!(url(open.open_search("jira:BITS-1").unwrap()), None);
+        assert_eq!(url(open.open_fallback("a b").unwrap()), None);
+
+        open.fallback = Some(Template::new(
+            "https://search.example.com/?q=<text|urlencode>",
+        ));
+        assert_eq!(
+            url(open.open_fallback("a b").unwrap()),
+            Some((
+                "open.fallback".to_string(),
+                "https://search.example.com/?q=a%20b".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_osc8() {
+        assert_eq!(
+            osc8("https://a.b/1", "TEST-1"),
+            "\x1b]8;;https://a.b/1\x1b\\TEST-1\x1b]8;;\x1b\\"
+        );
+        assert_eq!(
+            osc8("https://a.b/\x1b\\x", "a\x07b"),
+            "\x1b]8;;https://a.b/\\x\x1b\\ab\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_emit() {
+        let mut open = get_open_config();
+        open.launcher = Some(Emit::Osc8);
+        assert_eq!(open.emit(), Emit::Osc8);
+        open.set_emit(Some(Emit::Launch));
+        assert_eq!(open.emit(), Emit::Launch);
+    }
+
+    #[test]
+    fn test_words() {
+        let found = words("Fix (TEST-1) and #12, see `a.rs`.")
+            .map(|(_, w)| w)
+            .collect::<Vec<_>>();
+
+        assert_eq!(found, vec!["Fix", "TEST-1", "and", "#12", "see", "a.rs"]);
+        assert_eq!(words("  x").next(), Some((2, "x")));
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut open = get_open_config();
+        open.patterns.push(PatternOpen {
+            url: Template::new("https://example.com/issues/<pat1>"),
+            ..pattern(0, r"^#(\d+)$", MatchMode::Search)
+        });
+
+        let texts = vec![
+            "Fix test-1234 and #12, then test-1250 (not #a)".to_string(),
+            "again test-1250 and #12".to_string(),
+            "see footest-12, test-12".to_string(),
+        ];
+        assert_eq!(
+            open.extract(&texts).unwrap(),
+            vec!["test-1234", "#12", "test-1250", "test-12"]
+        );
+    }
+
+    #[test]
+    fn test_extract_paths() {
+        let mut open = get_open_config();
+        open.patterns = vec![
+            PatternOpen {
+                kind: Some(PathKind::File),
+                extension: vec!["toml".into()],
+                ..pattern(0, ".", MatchMode::Search)
+            },
+            PatternOpen {
+                kind: Some(PathKind::Dir),
+                ..pattern(0, ".", MatchMode::Search)
+            },
+            pattern(0, r"^(\d+)$", MatchMode::Search),
+        ];
+
+        let texts = vec!["see Cargo.toml, nope.toml and src/ in 1-2.".to_string()];
+        assert_eq!(open.extract(&texts).unwrap(), vec!["Cargo.toml", "src/"]);
+    }
+
+    #[test]
+    fn test_open_pattern() {
+        let open = get_open_config();
+        let can = open.open_pattern("test-123").unwrap();
+
+        assert_eq!(can.len(), 1);
+        assert_eq!(can[0].url, "https://example.com/123");
+        assert_eq!(can[0].priority, 1);
+        assert_eq!(can[0].rule, "open.patterns[0]");
+
+        let can = open.open_pattern("feature-123").unwrap();
+        assert!(can.is_empty());
+    }
+
+    fn pattern(priority: i32, pattern: &str, match_mode: MatchMode) -> PatternOpen {
+        PatternOpen {
+            priority,
+            match_mode,
+            ..PatternOpen::new(pattern, "https://example.com")
+        }
+    }
+
+    fn rules(can: &[CanOpen]) -> Vec<String> {
+        can.iter().map(|c| c.rule.clone()).collect()
+    }
+
+    #[test]
+    fn test_print_all() {
+        let mut open = get_open_config();
+        open.patterns.push(PatternOpen {
+            url: Template::new("https://other.com/<pat1>"),
+            ..pattern(0, r"^test-(\d+)$", MatchMode::Search)
+        });
+        let print = |texts: &[&str]| {
+            let texts = texts.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+            let mut out = Vec::new();
+            open.print_to(&mut out, &texts, true)
+                .map(|()| String::from_utf8(out).unwrap())
+        };
+
+        assert_eq!(
+            print(&["test-12"]).unwrap(),
+            "test-12\t1\topen.patterns[0]\thttps://example.com/12\n\
+             test-12\t0\topen.patterns[1]\thttps://other.com/12\n"
+        );
+        assert_eq!(
+            print(&["a test-3"]).unwrap(),
+            "a test-3\t1\topen.patterns[0]\thttps://example.com/3\n"
+        );
+        assert_eq!(
+            print(&["nope"]).unwrap_err().to_string(),
+            "Could not find pattern for nope"
+        );
+
+        let texts = ["test-1", "nope", "a test-3"].map(String::from);
+        let mut out = Vec::new();
+        let e = open.print_to(&mut out, &texts, true).unwrap_err();
+        assert_eq!(e.to_string(), "Could not resolve 1 text");
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "test-1\t1\topen.patterns[0]\thttps://example.com/1\n\
+             test-1\t0\topen.patterns[1]\thttps://other.com/1\n\
+             a test-3\t1\topen.patterns[0]\thttps://example.com/3\n"
+        );
+    }
+
+    #[test]
+    fn test_print_texts() {
+        let open = get_open_config();
+        let print = |texts: &[&str]| {
+            let texts = texts.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+            let mut out = Vec::new();
+            let result = open.print_to(&mut out, &texts, false);
+            (
+                String::from_utf8(out).unwrap(),
+                result.map_err(|e| e.to_string()),
+            )
+        };
+
+        assert_eq!(
+            print(&["test-1", "test-2", "see test-1"]),
+            (
+                "https://example.com/1\nhttps://example.com/2\n".to_string(),
+                Ok(())
+            )
+        );
+        assert_eq!(
+            print(&["test-1", "nope", "test-2", "nada"]),
+            (
+                "https://example.com/1\nhttps://example.com/2\n".to_string(),
+                Err("Could not resolve 2 texts".to_string())
+            )
+        );
+        assert_eq!(
+            print(&["nope", "nada"]),
+            (String::new(), Err("Nothing to open".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut open = get_open_config();
+        open.patterns
+            .push(pattern(0, r"^TEST-\d+$", MatchMode::Search));
+        let explain = |text: &str| {
+            let mut out = Vec::new();
+            open.explain_to(&mut out, &[text.to_string()]).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let out = explain("test-12");
+        assert!(out.starts_with("Input: test-12\n"), "{out}");
+        assert!(
+            out.contains("\nopen.patterns[0] `test-(\\d+)`: matched <pat1>=12\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("\nopen.patterns[1] `^TEST-\\d+$`: no match\n"),
+            "{out}"
+        );
+        assert!(
+            out.ends_with(
+                "Candidates (first is opened):\n  1. priority 1 open.patterns[0] https://example.com/12\n"
+            ),
+            "{out}"
+        );
+
+        let out = explain("nope");
+        assert!(
+            out.contains("open.patterns[0] `test-(\\d+)`: no match\n"),
+            "{out}"
+        );
+        assert!(
+            out.ends_with("Candidates (first is opened):\n  none\n"),
+            "{out}"
+        );
+
+        open.search = BTreeMap::from([(
+            "gh".to_string(),
+            SearchOpen {
+                url: Template::new("https://github.com/search?q=<query>"),
+            },
+        )]);
+        let explain = |text: &str| {
+            let mut out = Vec::new();
+            open.explain_to(&mut out, &[text.to_string()]).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let out = explain("gh:bits");
+        assert!(
+            out.contains("\nopen.search.gh `https://github.com/search?q=<query>`: matched"),
+            "{out}"
+        );
+        assert!(
+            out.ends_with("  1. priority 0 open.search.gh https://github.com/search?q=bits\n"),
+            "{out}"
+        );
+        let out = explain("gh: ");
+        assert!(!out.contains("open.search.gh"), "{out}");
+        assert!(out.ends_with("  none\n"), "{out}");
+    }
+
+    #[test]
+    fn test_open_pattern_order() {
+        let mut open = get_open_config();
+        open.patterns = vec![
+            pattern(0, "github", MatchMode::Search),
+            pattern(0, "^(https?://.+)$", MatchMode::Search),
+            pattern(0, r"github\.com/\w+", MatchMode::Search),
+            pattern(1, "x", MatchMode::Search),
+            pattern(0, r"github\.com/\w+", MatchMode::Search),
+        ];
+
+        let mut can = open.open_pattern("https://github.com/org/x").unwrap();
+        assert_eq!(
+            rules(&can),
+            vec![
+                "open.patterns[0]",
+                "open.patterns[1]",
+                "open.patterns[2]",
+                "open.patterns[3]",
+                "open.patterns[4]",
+            ]
+        );
+
+        sort_candidates(&mut can);
+        assert_eq!(
+            rules(&can),
+            vec![
+                "open.patterns[3]",
+                "open.patterns[1]",
+                "open.patterns[2]",
+                "open.patterns[4]",
+                "open.patterns[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_git_candidates_order() {
+        let mut open = get_open_config();
+        open.patterns = vec![pattern(0, r"^\d+$", MatchMode::Search)];
+        let section = |priority| GitOpen {
+            priority,
+            host: Some("host".into()),
+            url: Some(Template::new("https://host/<repo>")),
+            patterns: vec![pattern(0, r"\d+", MatchMode::Search)],
+            ..Default::default()
+        };
+        open.git = vec![
+            ("zeta".into(), section(0)),
+            ("alpha".into(), section(0)),
+            (
+                "other".into(),
+                GitOpen {
+                    host: Some("other".into()),
+                    ..section(5)
+                },
+            ),
+            ("beta".into(), section(-1)),
+        ];
+
+        let input = GitInput::Text("12".into());
+        let mut can = open
+            .git_candidates("12", &input, "git@host:org/repo.git")
+            .unwrap();
+        assert_eq!(
+            rules(&can),
+            vec![
+                "open.git.zeta.patterns[0]",
+                "open.git.alpha.patterns[0]",
+                "open.git.beta.patterns[0]",
+            ]
+        );
+
+        can.append(&mut open.open_pattern("12").unwrap());
+        sort_candidates(&mut can);
+        assert_eq!(
+            rules(&can),
+            vec![
+                "open.git.zeta.patterns[0]",
+                "open.git.alpha.patterns[0]",
+                "open.patterns[0]",
+                "open.git.beta.patterns[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_mode() {
+        let mut open = get_open_config();
+        open.patterns = vec![
+            pattern(0, r"TEST-\d+", MatchMode::Full),
+            pattern(0, r"TEST-\d+", MatchMode::Prefix),
+            pattern(0, r"TEST-\d+", MatchMode::Search),
+        ];
+        open.apply_match_modes();
+
+        let matching = |text| rules(&open.open_pattern(text).unwrap());
+        assert_eq!(
+            matching("TEST-1"),
+            vec!["open.patterns[0]", "open.patterns[1]", "open.patterns[2]"]
+        );
+        assert_eq!(
+            matching("TEST-1 fix"),
+            vec!["open.patterns[1]", "open.patterns[2]"]
+        );
+        assert_eq!(matching("fix TEST-1"), vec!["open.patterns[2]"]);
+    }
+
+    fn get_git_open_config() -> GitOpen {
+        GitOpen {
+            priority: 1,
+            remote: Some(ConfigRegex::new("https?://repo.com/(\\.*).git")),
+            url: Some(Template::new("https://repo.com/<r1>/")),
+            commit: Some(Template::new("https://repo.com/<r1>/<commit>")),
+            file: Some(Template::new("https://repo.com/<r1>/blob/<ref>/<path>")),
+            file_lines: Some(Template::new(
+                "https://repo.com/<r1>/blob/<commit>/<path>#L<start>-L<end>",
+            )),
+            patterns: vec![PatternOpen {
+                url: Template::new("https://repo.com/<r1>/p1/<pat1>"),
+                ..pattern(2, "^(\\d+)$", MatchMode::Search)
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// The field and url `git_open` resolves `input` to.
+    fn base(git_open: &GitOpen, input: &GitInput, vars: &Vars) -> Option<(String, String)> {
+        git_open
+            .get_base(input, vars)
+            .unwrap()
+            .map(|base| (base.field, base.url))
+    }
+
+    fn get_remote_vars() -> Vars {
+        Vars::from([("r1".to_string(), "org".to_string())])
+    }
+
+    #[test]
+    fn test_git_open_get_base_url() {
+        let git_open = get_git_open_config();
+        let vars = get_remote_vars();
+
+        assert_eq!(
+            base(&git_open, &GitInput::Repo { branch: None }, &vars),
+            Some(("url".to_string(), "https://repo.com/org/".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_git_get_base_commit() {
+        let git_open = get_git_open_config();
+        let vars = get_remote_vars();
+
+        assert_eq!(
+            base(&git_open, &GitInput::Commit("abc".into()), &vars),
+            Some(("commit".to_string(), "https://repo.com/org/abc".to_string()))
+        );
+        assert_eq!(
+            base(&git_open, &GitInput::Commit("helloa".into()), &vars),
+            Some((
+                "commit".to_string(),
+                "https://repo.com/org/helloa".to_string()
+            ))
+        );
+        assert_eq!(
+            base(&git_open, &GitInput::Commit("".into()), &vars),
+            Some(("commit".to_string(), "https://repo.com/org/".to_string()))
+        );
+        assert_eq!(
+            base(&git_open, &GitInput::Commit("1".into()), &vars),
+            Some(("commit".to_string(), "https://repo.com/org/1".to_string()))
+        );
+        assert_eq!(base(&git_open, &GitInput::Text("".into()), &vars), None,);
+        assert_eq!(base(&git_open, &GitInput::Text("abc".into()), &vars), None,);
+    }
+
+    #[test]
+    fn test_git_get_base_refs() {
+        let mut git_open = get_git_open_config();
+        let vars = get_remote_vars();
+        let branch = GitInput::Branch(BranchRef {
+            branch: "feat/a".into(),
+            remote: Some("origin".into()),
+            commit: "abc".into(),
+        });
+        let tag = GitInput::Tag {
+            tag: "v1.0".into(),
+            commit: "def".into(),
+        };
+        let range = GitInput::Range(RevRange {
+            base: "v1.0".into(),
+            head: "feat/a".into(),
+            dots: "..".into(),
+        });
+
+        assert_eq!(
+            base(&git_open, &branch, &vars),
+            Some(("commit".to_string(), "https://repo.com/org/abc".to_string()))
+        );
+        assert_eq!(
+            base(&git_open, &tag, &vars),
+            Some(("commit".to_string(), "https://repo.com/org/def".to_string()))
+        );
+        assert_eq!(base(&git_open, &range, &vars), None);
+
+        git_open.branch = Some(Template::new(
+            "https://repo.com/<r1>/tree/<branch>?remote=<remote>",
+        ));
+        git_open.tag = Some(Template::new("https://repo.com/<r1>/tag/<tag>"));
+        git_open.compare = Some(Template::new("https://repo.com/<r1>/<base><dots><head>"));
+        assert_eq!(
+            base(&git_open, &branch, &vars),
+            Some((
+                "branch".to_string(),
+                "https://repo.com/org/tree/feat/a?remote=origin".to_string()
+            ))
+        );
+        assert_eq!(
+            base(&git_open, &tag, &vars),
+            Some((
+                "tag".to_string(),
+                "https://repo.com/org/tag/v1.0".to_string()
+            ))
+        );
+        assert_eq!(
+            base(&git_open, &range, &vars),
+            Some((
+                "compare".to_string(),
+                "https://repo.com/org/v1.0..feat/a".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_git_get_base_pattern() {
+        let git_open = get_git_open_config();
+        let vars = get_remote_vars();
+
+        assert_eq!(
+            base(&git_open, &GitInput::Text("123".into()), &vars),
+            Some((
+                "patterns[0]".to_string(),
+                "https://repo.com/org/p1/123".to_string()
+            ))
+        );
+        assert_eq!(
+            base(&git_open, &GitInput::Text("1".into()), &vars),
+            Some((
+                "patterns[0]".to_string(),
+                "https://repo.com/org/p1/1".to_string()
+            ))
+        );
+        assert_eq!(base(&git_open, &GitInput::Text("a".into()), &vars), None);
+        assert_eq!(base(&git_open, &GitInput::Text("abc".into()), &vars), None,);
+    }
+
+    #[test]
+    fn test_git_named_groups() {
+        let git_open = GitOpen {
+            priority: 0,
+            remote: Some(ConfigRegex::new(
+                "^git@host:(?P<owner>.+)/(?P<repo>.+).git$",
+            )),
+            url: Some(Template::new("https://host/<owner>/<repo>")),
+            patterns: vec![PatternOpen {
+                url: Template::new("https://host/<owner>/<r2>/issues?q=<ticket>&t=<pat1>"),
+                ..pattern(0, r"^(?P<ticket>[A-Z]+-\d+)$", MatchMode::Search)
+            }],
+            ..Default::default()
+        };
+
+        let vars = git_open.remote_vars("git@host:org/proj.git").unwrap();
+        assert_eq!(git_open.remote_vars("https://host/org/proj"), None);
+        assert_eq!(
+            base(&git_open, &GitInput::Text("TEST-1".into()), &vars),
+            Some((
+                "patterns[0]".to_string(),
+                "https://host/org/proj/issues?q=TEST-1&t=TEST-1".to_string()
+            ))
+        );
+        assert!(base(&git_open, &GitInput::Commit("1".into()), &vars).is_none());
+    }
+
+    #[test]
+    fn test_git_get_base_filters() {
+        let mut git_open = get_git_open_config();
+        let vars = get_remote_vars();
+        git_open.patterns = vec![PatternOpen {
+            url: Template::new("https://repo.com/<r1|upper>/<pat1|upper>/<pat2|urlencode>"),
+            ..pattern(0, r"^([a-z]+-\d+) (.+)$", MatchMode::Search)
+        }];
+
+        assert_eq!(
+            base(&git_open, &GitInput::Text("test-1 feat/a b".into()), &vars),
+            Some((
+                "patterns[0]".to_string(),
+                "https://repo.com/ORG/TEST-1/feat%2Fa%20b".to_string()
+            ))
+        );
+        assert_eq!(
+            base(&git_open, &GitInput::Text("Test-1 a".into()), &vars),
+            None
+        );
+    }
+
+    #[test]
+    fn test_git_get_base_branch() {
+        let mut git_open = get_git_open_config();
+        let vars = get_remote_vars();
+        git_open.branch = Some(Template::new("https://repo.com/<r1>/tree/<branch>"));
+
+        assert_eq!(
+            base(
+                &git_open,
+                &GitInput::Repo {
+                    branch: Some("feat".into())
+                },
+                &vars
+            ),
+            Some((
+                "branch".to_string(),
+                "https://repo.com/org/tree/feat".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_git_get_base_file() {
+        let mut git_open = get_git_open_config();
+        let vars = get_remote_vars();
+        let mut file = RepoFile {
+            path: "src/a.rs".into(),
+            lines: None,
+            commit: "abc".into(),
+            branch: Some("main".into()),
+        };
+
+        assert_eq!(
+            base(&git_open, &GitInput::File(file.clone()), &vars),
+            Some((
+                "file".to_string(),
+                "https://repo.com/org/blob/main/src/a.rs".to_string()
+            ))
+        );
+
+        file.lines = Some((40, 60));
+        assert_eq!(
+            base(&git_open, &GitInput::File(file.clone()), &vars),
+            Some((
+                "file_lines".to_string(),
+                "https://repo.com/org/blob/abc/src/a.rs#L40-L60".to_string()
+            ))
+        );
+
+        git_open.file = None;
+        git_open.file_lines = None;
+        assert_eq!(base(&git_open, &GitInput::File(file), &vars), None);
+    }
+
+    #[test]
+    fn test_apply_presets() {
+        let mut open = get_open_config();
+        open.providers = vec!["github".into(), "gitlab".into()];
+        open.git.push((
+            "gitlab".into(),
+            GitOpen {
+                preset: Some("gitlab".into()),
+                host: Some("git.example.com".into()),
+                url: Some(Template::new("https://example.com/<repo>")),
+                ..Default::default()
+            },
+        ));
+        open.git.push((
+            "unknown".into(),
+            GitOpen {
+                preset: Some("nope".into()),
+                ..Default::default()
+            },
+        ));
+
+        let mut errors = ConfigErrors::new("");
+        open.apply_presets(&mut errors);
+        open.validate(&mut errors);
+        let errors = errors.into_result("config").unwrap_err().to_string();
+        assert!(errors.contains("open.git.unknown.preset: Unknown preset `nope`"));
+        assert!(errors.contains("open.git.unknown: missing `remote` or `host`"));
+
+        let names = open.git.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["gitlab", "unknown", "github"]);
+
+        let gitlab = &open.git[0].1;
+        let vars = gitlab
+            .remote_vars("git@git.example.com:org/repo.git")
+            .unwrap();
+        let repo = GitInput::Repo { branch: None };
+        assert_eq!(
+            base(gitlab, &repo, &vars),
+            Some(("url".to_string(), "https://example.com/repo".to_string()))
+        );
+        assert!(gitlab.commit.is_some());
+        assert_eq!(open.git[2].1.host.as_deref(), Some("github.com"));
+    }
+
+    #[test]
+    fn test_git_remote_vars() {
+        let mut git_open = GitOpen {
+            host: Some("Example.com".into()),
+            ..Default::default()
+        };
+
+        let vars = git_open
+            .remote_vars("ssh://git@example.com:2222/group/sub/repo.git")
+            .unwrap();
+        assert_eq!(vars["host"], "example.com");
+        assert_eq!(vars["port"], "2222");
+        assert_eq!(vars["owner"], "group");
+        assert_eq!(vars["subgroups"], "sub");
+        assert_eq!(vars["namespace"], "group/sub");
+        assert_eq!(vars["repo"], "repo");
+        assert!(git_open.remote_vars("git@other.com:org/repo").is_none());
+
+        git_open.remote = Some(ConfigRegex::new("^git@other.com:(?P<repo>.+)$"));
+        let vars = git_open.remote_vars("git@other.com:org/repo").unwrap();
+        assert_eq!(vars["repo"], "org/repo");
+        assert_eq!(vars["owner"], "org");
+        assert_eq!(vars["r1"], "org/repo");
+    }
+
+    #[test]
+    fn test_git_get_base_pr() {
+        let mut git_open = get_git_open_config();
+        let vars = get_remote_vars();
+        let mut pr = BranchPr {
+            branch: "feat".into(),
+            upstream: "me/feat".into(),
+            number: None,
+        };
+
+        assert_eq!(
+            base(&git_open, &GitInput::PullRequest(pr.clone()), &vars),
+            None
+        );
+
+        git_open.pr = Some(Template::new("https://repo.com/<r1>/pull/<pr>"));
+        git_open.new_pr = Some(Template::new("https://repo.com/<r1>/compare/<upstream>"));
+        assert_eq!(
+            base(&git_open, &GitInput::PullRequest(pr.clone()), &vars),
+            Some((
+                "new_pr".to_string(),
+                "https://repo.com/org/compare/me/feat".to_string()
+            ))
+        );
+
+        pr.number = Some(7);
+        assert_eq!(
+            base(&git_open, &GitInput::PullRequest(pr), &vars),
+            Some(("pr".to_string(), "https://repo.com/org/pull/7".to_string()))
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::open::template::Vars;
+
+/// What a pattern's text has to be on the local filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathKind {
+    /// Anything that exists
+    Any,
+    File,
+    Dir,
+}
+
+/// The variables for `text` if it is an existing path of `kind` with one of
+/// `extensions` (case insensitive, without the `.`, any if empty):
+/// `<abspath>`, `<dirname>`, `<basename>`, `<stem>` and `<ext>`.
+pub fn path_vars(text: &str, kind: Option<PathKind>, extensions: &[String]) -> Option<Vars> {
+    let path = expand_path(text).ok()?.canonicalize().ok()?;
+    let meta = path.metadata().ok()?;
+
+    let is_kind = match kind.unwrap_or(PathKind::Any) {
+        PathKind::Any => true,
+        PathKind::File => meta.is_file(),
+        PathKind::Dir => meta.is_dir(),
+    };
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let has_extension = extensions.is_empty()
+        || extensions
+            .iter()
+            .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext));
+    if !is_kind || !has_extension {
+        return None;
+    }
+
+    let mut vars = Vars::new();
+    let mut insert = |name: &str, value: Option<&OsStr>| {
+        let value = value.map(|v| v.to_string_lossy().into_owned());
+        vars.insert(name.to_string(), value.unwrap_or_default());
+    };
+    insert("abspath", Some(path.as_os_str()));
+    insert("dirname", path.parent().map(Path::as_os_str));
+    insert("basename", path.file_name());
+    insert("stem", path.file_stem());
+    insert("ext", path.extension());
+
+    Some(vars)
+}
+
+/// Expand `~` and environment variables, relative paths are made
+/// absolute against the current directory.
+pub fn expand_path(s: &str) -> Result<PathBuf> {
+    let expanded = expand_env(s)?;
+
+    let path = match expanded.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            let home =
+                dirs::home_dir().ok_or_else(|| anyhow!("Could not find the home directory"))?;
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(expanded),
+    };
+
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Ok(env::current_dir()?.join(path))
+    }
+}
+
+/// Replace `$NAME` and `${NAME}` with the environment variable `NAME`.
+pub fn expand_env(s: &str) -> Result<String> {
+    expand_vars(s, |name| env::var(name).ok())
+}
+
+/// Replace `$NAME` and `${NAME}` with what `lookup` gives for `NAME`.
+fn expand_vars(s: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let (name, len) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| anyhow!("Unclosed `${{` in `{s}`"))?;
+                (&braced[..end], end + 2)
+            }
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], end)
+            }
+        };
+
+        if name.is_empty() {
+            out.push('$');
+            continue;
+        }
+
+        let value = lookup(name)
+            .ok_or_else(|| anyhow!("The environment variable `{name}` in `{s}` is not set"))?;
+        out.push_str(&value);
+        rest = &rest[len..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_expand_env() {
+        let expand = |s: &str| expand_vars(s, |name| (name == "EDITOR").then(|| "nvim".into()));
+
+        assert_eq!(expand("$EDITOR").unwrap(), "nvim");
+        assert_eq!(expand("${EDITOR}-qt").unwrap(), "nvim-qt");
+        assert_eq!(expand("/a/$EDITOR/b").unwrap(), "/a/nvim/b");
+        assert_eq!(expand("a$ b$").unwrap(), "a$ b$");
+        assert!(expand("$BITS_TEST_UNSET").is_err());
+        assert!(expand("${EDITOR").is_err());
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_path("~").unwrap(), home);
+        assert_eq!(expand_path("~/a/b").unwrap(), home.join("a/b"));
+        assert_eq!(
+            expand_path("a").unwrap(),
+            env::current_dir().unwrap().join("a")
+        );
+        assert_eq!(expand_path("/a/~").unwrap(), PathBuf::from("/a/~"));
+    }
+
+    #[test]
+    fn test_path_vars() {
+        let dir = env::temp_dir().join(format!("bits-path-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("Doc.PDF");
+        fs::write(&file, "").unwrap();
+        let file = file.to_str().unwrap();
+
+        let vars = |kind, extensions: &[&str], text: &str| {
+            let extensions = extensions.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            path_vars(text, kind, &extensions)
+        };
+
+        let found = vars(Some(PathKind::File), &["pdf"], file).unwrap();
+        assert_eq!(found["basename"], "Doc.PDF");
+        assert_eq!(found["stem"], "Doc");
+        assert_eq!(found["ext"], "PDF");
+        assert_eq!(
+            found["dirname"],
+            dir.canonicalize().unwrap().to_str().unwrap()
+        );
+
+        assert!(vars(None, &[".png", ".pdf"], file).is_some());
+        assert!(vars(None, &["png"], file).is_none());
+        assert!(vars(Some(PathKind::Dir), &[], file).is_none());
+        assert!(vars(Some(PathKind::Dir), &[], dir.to_str().unwrap()).is_some());
+        assert!(vars(Some(PathKind::Any), &[], "/nonexistent/bits").is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+use crate::config::ConfigRegex;
+use crate::open::template::Template;
+use crate::open::{GitOpen, PatternOpen};
+
+/// The names accepted by `preset` and `providers`.
+pub const PRESETS: [&str; 6] = ["github", "gitlab", "gitea", "forgejo", "bitbucket", "azure"];
+
+/// Built-in settings for a git forge, `host` replaces the default host.
+pub fn preset(name: &str, host: Option<&str>) -> Option<GitOpen> {
+    let git_open = match name {
+        "github" => github(host.unwrap_or("github.com")),
+        "gitlab" => gitlab(host.unwrap_or("gitlab.com")),
+        "gitea" => gitea(host.unwrap_or("gitea.com")),
+        "forgejo" => gitea(host.unwrap_or("codeberg.org")),
+        "bitbucket" => bitbucket(host.unwrap_or("bitbucket.org")),
+        "azure" => azure(host.unwrap_or("dev.azure.com")),
+        _ => return None,
+    };
+
+    Some(git_open)
+}
+
+fn github(host: &str) -> GitOpen {
+    let base = format!("https://{host}/<namespace>/<repo>");
+    GitOpen {
+        host: Some(host.to_string()),
+        url: Some(Template::new(&base)),
+        branch: Some(Template::new(&format!("{base}/tree/<branch|pathencode>"))),
+        commit: Some(Template::new(&format!("{base}/commit/<commit>"))),
+        tag: Some(Template::new(&format!(
+            "{base}/releases/tag/<tag|pathencode>"
+        ))),
+        compare: Some(Template::new(&format!(
+            "{base}/compare/<base|pathencode><dots><head|pathencode>"
+        ))),
+        file: Some(Template::new(&format!(
+            "{base}/blob/<ref|pathencode>/<path|pathencode>"
+        ))),
+        file_lines: Some(Template::new(&format!(
+            "{base}/blob/<commit>/<path|pathencode>#L<start>-L<end>"
+        ))),
+        pr: Some(Template::new(&format!("{base}/pull/<pr>"))),
+        new_pr: Some(Template::new(&format!(
+            "{base}/compare/<upstream|pathencode>?expand=1"
+        ))),
+        patterns: vec![
+            PatternOpen::new(r"^(\d+)$", &format!("{base}/pull/<pat1>")),
+            PatternOpen::new(r"^#(\d+)$", &format!("{base}/issues/<pat1>")),
+        ],
+        ..Default::default()
+    }
+}
+
+fn gitlab(host: &str) -> GitOpen {
+    let base = format!("https://{host}/<namespace>/<repo>");
+    GitOpen {
+        host: Some(host.to_string()),
+        url: Some(Template::new(&base)),
+        branch: Some(Template::new(&format!("{base}/-/tree/<branch|pathencode>"))),
+        commit: Some(Template::new(&format!("{base}/-/commit/<commit>"))),
+        tag: Some(Template::new(&format!("{base}/-/tags/<tag|pathencode>"))),
+        compare: Some(Template::new(&format!(
+            "{base}/-/compare/<base|pathencode>...<head|pathencode>"
+        ))),
+        file: Some(Template::new(&format!(
+            "{base}/-/blob/<ref|pathencode>/<path|pathencode>"
+        ))),
+        file_lines: Some(Template::new(&format!(
+            "{base}/-/blob/<commit>/<path|pathencode>#L<start>-<end>"
+        ))),
+        pr: Some(Template::new(&format!("{base}/-/merge_requests/<pr>"))),
+        new_pr: Some(Template::new(&format!(
+            "{base}/-/merge_requests/new?merge_request%5Bsource_branch%5D=<upstream|urlencode>"
+        ))),
+        patterns: vec![
+            PatternOpen::new(r"^!?(\d+)$", &format!("{base}/-/merge_requests/<pat1>")),
+            PatternOpen::new(r"^#(\d+)$", &format!("{base}/-/issues/<pat1>")),
+        ],
+        ..Default::default()
+    }
+}
+
+fn gitea(host: &str) -> GitOpen {
+    let base = format!("https://{host}/<namespace>/<repo>");
+    GitOpen {
+        host: Some(host.to_string()),
+        url: Some(Template::new(&base)),
+        branch: Some(Template::new(&format!(
+            "{base}/src/branch/<branch|pathencode>"
+        ))),
+        commit: Some(Template::new(&format!("{base}/commit/<commit>"))),
+        tag: Some(Template::new(&format!(
+            "{base}/releases/tag/<tag|pathencode>"
+        ))),
+        compare: Some(Template::new(&format!(
+            "{base}/compare/<base|pathencode>...<head|pathencode>"
+        ))),
+        file: Some(Template::new(&format!(
+            "{base}/src/commit/<commit>/<path|pathencode>"
+        ))),
+        file_lines: Some(Template::new(&format!(
+            "{base}/src/commit/<commit>/<path|pathencode>#L<start>-L<end>"
+        ))),
+        pr: Some(Template::new(&format!("{base}/pulls/<pr>"))),
+        new_pr: Some(Template::new(&format!(
+            "{base}/compare/<upstream|pathencode>"
+        ))),
+        patterns: vec![
+            PatternOpen::new(r"^(\d+)$", &format!("{base}/pulls/<pat1>")),
+            PatternOpen::new(r"^#(\d+)$", &format!("{base}/issues/<pat1>")),
+        ],
+        ..Default::default()
+    }
+}
+
+fn bitbucket(host: &str) -> GitOpen {
+    let base = format!("https://{host}/<namespace>/<repo>");
+    GitOpen {
+        host: Some(host.to_string()),
+        url: Some(Template::new(&base)),
+        branch: Some(Template::new(&format!("{base}/src/<branch|pathencode>"))),
+        commit: Some(Template::new(&format!("{base}/commits/<commit>"))),
+        tag: Some(Template::new(&format!("{base}/src/<tag|pathencode>"))),
+        compare: Some(Template::new(&format!(
+            "{base}/branches/compare/<head|urlencode>%0D<base|urlencode>"
+        ))),
+        file: Some(Template::new(&format!(
+            "{base}/src/<commit>/<path|pathencode>"
+        ))),
+        file_lines: Some(Template::new(&format!(
+            "{base}/src/<commit>/<path|pathencode>#lines-<start>:<end>"
+        ))),
+        pr: Some(Template::new(&format!("{base}/pull-requests/<pr>"))),
+        new_pr: Some(Template::new(&format!(
+            "{base}/pull-requests/new?source=<upstream|urlencode>"
+        ))),
+        patterns: vec![
+            PatternOpen::new(r"^(\d+)$", &format!("{base}/pull-requests/<pat1>")),
+            PatternOpen::new(r"^#(\d+)$", &format!("{base}/issues/<pat1>")),
+        ],
+        ..Default::default()
+    }
+}
+
+fn azure(host: &str) -> GitOpen {
+    let project = format!("https://{host}/<owner>/<project>");
+    let base = format!("{project}/_git/<repo>");
+    let remote = ConfigRegex::new(&format!(
+        r"^(?:git@ssh\.{host}:v3/|https://(?:[^@/]+@)?{host}/)(?P<owner>[^/]+)/(?P<project>[^/]+)/(?:_git/)?(?P<repo>[^/]+?)/?$",
+        host = regex::escape(host)
+    ));
+
+    GitOpen {
+        remote: Some(remote),
+        url: Some(Template::new(&base)),
+        branch: Some(Template::new(&format!(
+            "{base}?version=GB<branch|urlencode>"
+        ))),
+        commit: Some(Template::new(&format!("{base}/commit/<commit>"))),
+        tag: Some(Template::new(&format!("{base}?version=GT<tag|urlencode>"))),
+        compare: Some(Template::new(&format!(
+            "{base}/branchCompare?baseVersion=GB<base|urlencode>&targetVersion=GB<head|urlencode>"
+        ))),
+        file: Some(Template::new(&format!(
+            "{base}?path=/<path|urlencode>&version=GC<commit>"
+        ))),
+        file_lines: Some(Template::new(&format!(
+            "{base}?path=/<path|urlencode>&version=GC<commit>&line=<start>&lineEnd=<end>&lineStartColumn=1&lineEndColumn=1"
+        ))),
+        pr: Some(Template::new(&format!("{base}/pullrequest/<pr>"))),
+        new_pr: Some(Template::new(&format!(
+            "{base}/pullrequestcreate?sourceRef=<upstream|urlencode>"
+        ))),
+        patterns: vec![
+            PatternOpen::new(r"^(\d+)$", &format!("{base}/pullrequest/<pat1>")),
+            PatternOpen::new(r"^#(\d+)$", &format!("{project}/_workitems/edit/<pat1>")),
+        ],
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::open::input::{GitInput, RevRange};
+
+    fn resolve(git_open: &GitOpen, remote: &str, input: GitInput) -> Option<String> {
+        let vars = git_open.remote_vars(remote)?;
+        git_open
+            .get_base(&input, &vars)
+            .unwrap()
+            .map(|base| base.url)
+    }
+
+    #[test]
+    fn test_preset_remotes() {
+        let github = preset("github", None).unwrap();
+        let repo = || GitInput::Repo { branch: None };
+
+        for remote in [
+            "git@github.com:org/repo.git",
+            "ssh://git@github.com/org/repo.git",
+            "ssh://git@github.com:22/org/repo",
+            "https://github.com/org/repo.git",
+            "https://user@github.com/org/repo/",
+        ] {
+            assert_eq!(
+                resolve(&github, remote, repo()).as_deref(),
+                Some("https://github.com/org/repo"),
+                "{remote}"
+            );
+        }
+        assert_eq!(
+            resolve(&github, "git@gitlab.com:org/repo.git", repo()),
+            None
+        );
+
+        let gitlab = preset("gitlab", Some("git.example.com")).unwrap();
+        assert_eq!(
+            resolve(
+                &gitlab,
+                "git@git.example.com:group/sub/repo.git",
+                GitInput::Text("!12".into())
+            )
+            .as_deref(),
+            Some("https://git.example.com/group/sub/repo/-/merge_requests/12")
+        );
+
+        let azure = preset("azure", None).unwrap();
+        for remote in [
+            "git@ssh.dev.azure.com:v3/org/proj/repo",
+            "https://org@dev.azure.com/org/proj/_git/repo",
+        ] {
+            assert_eq!(
+                resolve(&azure, remote, repo()).as_deref(),
+                Some("https://dev.azure.com/org/proj/_git/repo"),
+                "{remote}"
+            );
+        }
+
+        let tag = GitInput::Tag {
+            tag: "v1.0".into(),
+            commit: "abc".into(),
+        };
+        assert_eq!(
+            resolve(&github, "git@github.com:org/repo.git", tag).as_deref(),
+            Some("https://github.com/org/repo/releases/tag/v1.0")
+        );
+        let range = GitInput::Range(RevRange {
+            base: "main".into(),
+            head: "feat/a".into(),
+            dots: "...".into(),
+        });
+        assert_eq!(
+            resolve(&github, "git@github.com:org/repo.git", range.clone()).as_deref(),
+            Some("https://github.com/org/repo/compare/main...feat/a")
+        );
+        assert_eq!(
+            resolve(&gitlab, "git@git.example.com:group/repo.git", range).as_deref(),
+            Some("https://git.example.com/group/repo/-/compare/main...feat/a")
+        );
+
+        assert!(preset("nope", None).is_none());
+    }
+}
@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::ops::Range;
+use toml::Spanned;
+
+use crate::config::{ConfigErrors, ConfigRegex};
+
+/// Values bound to the placeholders of a [`Template`].
+pub type Vars = BTreeMap<String, String>;
+
+/// A url template where every `<name>` is replaced by the variable `name`.
+///
+/// Filters can be applied to the value, `<name|filter|filter>`, see [`FILTERS`].
+/// Anything between `<` and `>` that is not a valid placeholder is kept as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template(Spanned<String>);
+
+/// The filters that can be applied to a placeholder.
+pub const FILTERS: [&str; 5] = ["urlencode", "pathencode", "upper", "lower", "slug"];
+
+impl Template {
+    pub fn new(template: &str) -> Self {
+        Self(Spanned::new(0..0, template.to_string()))
+    }
+
+    fn as_str(&self) -> &str {
+        self.0.get_ref()
+    }
+
+    pub fn render(&self, vars: &Vars) -> Result<String> {
+        let template = self.as_str();
+        let mut out = String::with_capacity(template.len());
+        let mut last = 0;
+
+        for (range, name, filters) in self.placeholders() {
+            let value = vars.get(name).ok_or_else(|| {
+                let known = vars
+                    .keys()
+                    .map(|k| format!("<{k}>"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                anyhow!("Unknown placeholder `<{name}>` in `{self}`, known are: {known}")
+            })?;
+
+            let mut value = value.clone();
+            for filter in filters {
+                value = apply_filter(filter, &value)
+                    .ok_or_else(|| anyhow!("Unknown filter `{filter}` in `{self}`"))?;
+            }
+
+            out.push_str(&template[last..range.start]);
+            out.push_str(&value);
+            last = range.end;
+        }
+
+        out.push_str(&template[last..]);
+        Ok(out)
+    }
+
+    /// Report unknown filters.
+    pub fn validate(&self, path: &str, errors: &mut ConfigErrors) {
+        for (_, name, filters) in self.placeholders() {
+            for filter in filters {
+                if !FILTERS.contains(&filter) {
+                    errors.push(
+                        path,
+                        self.0.span(),
+                        format!(
+                            "Unknown filter `{filter}` for `<{name}>`, expected one of: {}",
+                            FILTERS.join(", ")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The range, name and filters of every placeholder.
+    fn placeholders(&self) -> impl Iterator<Item = (Range<usize>, &str, Vec<&str>)> {
+        let s = self.as_str();
+        s.match_indices('<').filter_map(move |(start, _)| {
+            let len = s[start + 1..].find(['<', '>'])?;
+            let end = start + len + 2;
+            if !s[end - 1..].starts_with('>') {
+                return None;
+            }
+
+            let mut parts = s[start + 1..end - 1].split('|').map(str::trim);
+            let name = parts.next()?;
+            let filters = parts.collect::<Vec<_>>();
+
+            (is_name(name) && filters.iter().all(|f| is_name(f))).then_some((
+                start..end,
+                name,
+                filters,
+            ))
+        })
+    }
+}
+
+impl Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Template {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Spanned::<String>::deserialize(deserializer).map(Self)
+    }
+}
+
+impl Serialize for Template {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_str().serialize(serializer)
+    }
+}
+
+fn apply_filter(filter: &str, value: &str) -> Option<String> {
+    let value = match filter {
+        "urlencode" => percent_encode(value, false),
+        "pathencode" => percent_encode(value, true),
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        "slug" => slug(value),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Percent-encode everything but unreserved characters,
+/// and `/` if `keep_slash`.
+fn percent_encode(value: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char);
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    out
+}
+
+/// Lowercase, with every run of other characters than letters and digits
+/// replaced by a single `-`.
+fn slug(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Match `regex` against `text` and bind every group,
+/// numbered groups as `<{prefix}N>` and named groups by their name.
+pub fn capture_vars(prefix: &str, regex: &ConfigRegex, text: &str) -> Option<Vars> {
+    let groups = regex.captures(text)?;
+    let mut vars = Vars::new();
+
+    for (i, name) in regex.capture_names().enumerate().skip(1) {
+        let value = groups.get(i).map(|g| g.as_str()).unwrap_or_default();
+        if let Some(name) = name {
+            vars.insert(name.to_string(), value.to_string());
+        }
+        vars.insert(format!("{prefix}{i}"), value.to_string());
+    }
+
+    Some(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vars {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_every_occurrence() {
+        let template = Template::new("https://<host>/<r1>/<r1>?q=<pat1>");
+        let vars = vars(&[("host", "a.com"), ("r1", "x"), ("pat1", "1")]);
+
+        assert_eq!(template.render(&vars).unwrap(), "https://a.com/x/x?q=1");
+    }
+
+    #[test]
+    fn test_render_keeps_non_placeholders() {
+        let template = Template::new("a < b <not a name> <<r1>> <r1");
+        let vars = vars(&[("r1", "x")]);
+
+        assert_eq!(
+            template.render(&vars).unwrap(),
+            "a < b <not a name> <x> <r1"
+        );
+    }
+
+    #[test]
+    fn test_render_unknown_placeholder() {
+        let template = Template::new("https://<host>/<nope>");
+        let err = template.render(&vars(&[("host", "a")])).unwrap_err();
+
+        assert!(err.to_string().contains("`<nope>`"));
+    }
+
+    #[test]
+    fn test_render_filters() {
+        let vars = vars(&[("branch", "feat/a b#1"), ("pat1", "Test-12")]);
+
+        let render = |t: &str| Template::new(t).render(&vars).unwrap();
+        assert_eq!(render("<branch|urlencode>"), "feat%2Fa%20b%231");
+        assert_eq!(render("<branch|pathencode>"), "feat/a%20b%231");
+        assert_eq!(render("<pat1|upper>"), "TEST-12");
+        assert_eq!(render("<pat1|lower>"), "test-12");
+        assert_eq!(render("<branch|slug>"), "feat-a-b-1");
+        assert_eq!(render("<pat1 | lower | urlencode>"), "test-12");
+        assert_eq!(render("<pat1|>"), "<pat1|>");
+
+        assert!(Template::new("<pat1|nope>").render(&vars).is_err());
+    }
+
+    #[test]
+    fn test_percent_encode_utf8() {
+        assert_eq!(percent_encode("å ~", false), "%C3%A5%20~");
+    }
+
+    #[test]
+    fn test_validate_filters() {
+        let mut errors = ConfigErrors::new("");
+        Template::new("<a|upper>/<b|nope>").validate("url", &mut errors);
+
+        assert!(errors.into_result("config").is_err());
+    }
+
+    #[test]
+    fn test_capture_vars() {
+        let regex = ConfigRegex::new(r"(?P<ticket>[A-Z]+-\d+)(?:/(\d+))?");
+
+        assert_eq!(
+            capture_vars("pat", &regex, "TEST-12"),
+            Some(vars(&[
+                ("ticket", "TEST-12"),
+                ("pat1", "TEST-12"),
+                ("pat2", "")
+            ]))
+        );
+        assert_eq!(capture_vars("pat", &regex, "none"), None);
+    }
+}
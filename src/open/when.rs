@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::env::{self, consts};
+use std::fs;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::config::{ConfigErrors, ConfigRegex};
+use crate::git::Git;
+use crate::glob::Glob;
+use crate::open::path::expand_path;
+
+/// Conditions on where `bits` runs, every one that is set must hold.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct When {
+    /// `linux`, `macos`, `windows`, ...
+    os: Option<String>,
+    /// Running in the Windows Subsystem for Linux
+    wsl: Option<bool>,
+    /// Running in an SSH session
+    ssh_session: Option<bool>,
+    /// `NAME` must be set, `NAME=value` must have the value
+    env: Vec<String>,
+    /// Regex matched against the hostname
+    hostname: Option<ConfigRegex>,
+    /// The current directory is inside a git repository
+    in_git_repo: Option<bool>,
+    /// Glob matched against the current directory, can start with `~`
+    cwd: Option<Glob>,
+}
+
+/// What the conditions are checked against, detected once per invocation.
+#[derive(Debug, Default)]
+pub struct Context {
+    os: String,
+    wsl: bool,
+    ssh_session: bool,
+    env: Vec<(String, String)>,
+    hostname: String,
+    in_git_repo: bool,
+    cwd: String,
+    /// The current directory relative to the root of the repository, `""` at the root
+    repo_path: Option<String>,
+}
+
+impl When {
+    /// Whether every condition holds for the current invocation.
+    pub fn holds(&self) -> bool {
+        self.holds_in(Context::current())
+    }
+
+    fn holds_in(&self, context: &Context) -> bool {
+        let env = self.env.iter().all(|var| match var.split_once('=') {
+            Some((name, value)) => context.env.iter().any(|(n, v)| n == name && v == value),
+            None => context.env.iter().any(|(n, _)| n == var),
+        });
+        let cwd = self.cwd.as_ref().is_none_or(|glob| {
+            let home = expand_path("~").map(|home| home.to_string_lossy().into_owned());
+            let glob = match (glob.to_string().strip_prefix('~'), home) {
+                (Some(rest), Ok(home)) => Glob::new(&format!("{home}{rest}")),
+                _ => glob.clone(),
+            };
+            glob.is_match(&context.cwd)
+        });
+        let hostname = self
+            .hostname
+            .as_ref()
+            .is_none_or(|regex| regex.captures(&context.hostname).is_some());
+
+        self.os
+            .as_ref()
+            .is_none_or(|os| os.eq_ignore_ascii_case(&context.os))
+            && self.wsl.is_none_or(|wsl| wsl == context.wsl)
+            && self
+                .ssh_session
+                .is_none_or(|ssh| ssh == context.ssh_session)
+            && env
+            && hostname
+            && self
+                .in_git_repo
+                .is_none_or(|git| git == context.in_git_repo)
+            && cwd
+    }
+
+    pub fn validate(&self, path: &str, errors: &mut ConfigErrors) {
+        if let Some(hostname) = &self.hostname {
+            hostname.validate(&format!("{path}.hostname"), errors);
+        }
+    }
+}
+
+/// Whether `when` is unset or holds.
+pub fn holds(when: &Option<When>) -> bool {
+    when.as_ref().is_none_or(When::holds)
+}
+
+/// Whether `paths` is empty or one of them matches the current directory,
+/// relative to the root of the repository.
+pub fn in_paths(paths: &[Glob]) -> bool {
+    // Detecting the context runs git, only do it when a path is given
+    paths.is_empty() || in_paths_of(paths, Context::current())
+}
+
+fn in_paths_of(paths: &[Glob], context: &Context) -> bool {
+    paths.is_empty()
+        || context
+            .repo_path
+            .as_ref()
+            .is_some_and(|path| paths.iter().any(|glob| glob.is_match(path)))
+}
+
+impl Context {
+    pub fn current() -> &'static Self {
+        static CONTEXT: OnceLock<Context> = OnceLock::new();
+        CONTEXT.get_or_init(Self::detect)
+    }
+
+    fn detect() -> Self {
+        let wsl = env::var_os("WSL_DISTRO_NAME").is_some()
+            || fs::read_to_string("/proc/sys/kernel/osrelease")
+                .is_ok_and(|release| release.to_lowercase().contains("microsoft"));
+        let ssh_session = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+            .iter()
+            .any(|var| env::var_os(var).is_some());
+
+        let toplevel = Git::toplevel().ok().flatten();
+        let cwd = env::current_dir().ok();
+        // The toplevel has its symlinks resolved
+        let repo_path = toplevel.as_ref().and_then(|toplevel| {
+            let cwd = cwd.as_ref()?.canonicalize().ok()?;
+            let toplevel = toplevel.canonicalize().ok()?;
+            let path = cwd.strip_prefix(toplevel).ok()?;
+            Some(path.to_string_lossy().into_owned())
+        });
+
+        Self {
+            os: consts::OS.to_string(),
+            wsl,
+            ssh_session,
+            env: env::vars().collect(),
+            hostname: hostname(),
+            in_git_repo: toplevel.is_some(),
+            cwd: cwd
+                .map(|cwd| cwd.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            repo_path,
+        }
+    }
+}
+
+fn hostname() -> String {
+    let from_file = ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|file| fs::read_to_string(file).ok());
+    let from_command = || {
+        Command::new("hostname")
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    from_file
+        .or_else(from_command)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> Context {
+        Context {
+            os: "linux".into(),
+            wsl: true,
+            ssh_session: false,
+            env: vec![
+                ("TERM".into(), "xterm".into()),
+                ("DISPLAY".into(), ":0".into()),
+            ],
+            hostname: "work-laptop".into(),
+            in_git_repo: true,
+            cwd: "/home/me/work/bits".into(),
+            repo_path: Some("services/billing/api".into()),
+        }
+    }
+
+    fn when(toml: &str) -> When {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_when() {
+        let context = context();
+
+        assert!(when("").holds_in(&context));
+        assert!(when("os = 'Linux'\nwsl = true").holds_in(&context));
+        assert!(!when("os = 'macos'").holds_in(&context));
+        assert!(!when("ssh_session = true").holds_in(&context));
+        assert!(when("env = ['DISPLAY', 'TERM=xterm']").holds_in(&context));
+        assert!(!when("env = ['TERM=kitty']").holds_in(&context));
+        assert!(!when("env = ['WAYLAND_DISPLAY']").holds_in(&context));
+        assert!(when("hostname = '^work-'").holds_in(&context));
+        assert!(!when("hostname = '^home-'").holds_in(&context));
+        assert!(when("in_git_repo = true\ncwd = '/home/*/work/**'").holds_in(&context));
+        assert!(!when("cwd = '/srv/**'").holds_in(&context));
+        assert!(toml::from_str::<When>("nope = true").is_err());
+    }
+
+    #[test]
+    fn test_in_paths() {
+        let mut context = context();
+        let globs = |globs: &[&str]| globs.iter().map(|g| Glob::new(g)).collect::<Vec<_>>();
+
+        assert!(in_paths_of(&[], &context));
+        assert!(in_paths_of(&globs(&["services/billing/**"]), &context));
+        assert!(in_paths_of(&globs(&["web/**", "services/*/api"]), &context));
+        assert!(!in_paths_of(&globs(&["services/billing"]), &context));
+
+        context.repo_path = Some(String::new());
+        assert!(!in_paths_of(&globs(&["services/billing/**"]), &context));
+        assert!(in_paths_of(&globs(&["**"]), &context));
+
+        context.repo_path = None;
+        assert!(!in_paths_of(&globs(&["**"]), &context));
+        assert!(in_paths_of(&[], &context));
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Result};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+pub struct Picker {}
+
+impl Picker {
+    /// Let the user pick one of `items`, returns the chosen index.
+    ///
+    /// Uses `command` (e.g. `["fzf"]`) when given, which receives one
+    /// numbered item per line on stdin and prints the chosen line.
+    /// Otherwise a numbered list is shown on the terminal.
+    pub fn pick(items: &[String], command: Option<&[String]>) -> Result<usize> {
+        match command {
+            Some(command) => Self::pick_command(items, command),
+            None => Self::pick_tty(items),
+        }
+    }
+
+    /// Whether the user can be asked to pick anything.
+    pub fn is_interactive() -> bool {
+        io::stdin().is_terminal() && io::stderr().is_terminal()
+    }
+
+    /// Ask a yes/no question, defaulting to no.
+    /// Reads the answer from the terminal even if stdin is a pipe.
+    pub fn confirm(question: &str) -> Result<bool> {
+        let mut stderr = io::stderr();
+        write!(stderr, "{question} [y/N]: ")?;
+        stderr.flush()?;
+
+        let mut line = String::new();
+        if io::stdin().is_terminal() {
+            io::stdin().lock().read_line(&mut line)?;
+        } else {
+            let tty = File::open("/dev/tty")
+                .map_err(|e| anyhow!("Could not ask for confirmation: {e}"))?;
+            BufReader::new(tty).read_line(&mut line)?;
+        }
+
+        Ok(matches!(line.trim(), "y" | "Y" | "yes"))
+    }
+
+    fn pick_command(items: &[String], command: &[String]) -> Result<usize> {
+        let Some((program, args)) = command.split_first() else {
+            return Err(anyhow!("The picker command is empty"));
+        };
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Could not run picker `{program}`: {e}"))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            for (i, item) in items.iter().enumerate() {
+                writeln!(stdin, "{}\t{item}", i + 1)?;
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(anyhow!("Nothing was picked"));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let chosen = stdout.trim().split('\t').next().unwrap_or_default();
+        Self::parse_choice(chosen, items.len())
+    }
+
+    fn pick_tty(items: &[String]) -> Result<usize> {
+        let mut stderr = io::stderr();
+        for (i, item) in items.iter().enumerate() {
+            writeln!(stderr, "{:3}) {item}", i + 1)?;
+        }
+        write!(stderr, "Pick [1-{}, default 1]: ", items.len())?;
+        stderr.flush()?;
+
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line)?;
+        Self::parse_answer(line.trim(), items.len())
+    }
+
+    /// The choice typed on the terminal, just Enter picks the first.
+    fn parse_answer(answer: &str, len: usize) -> Result<usize> {
+        match answer {
+            "" => Ok(0),
+            answer => Self::parse_choice(answer, len),
+        }
+    }
+
+    fn parse_choice(chosen: &str, len: usize) -> Result<usize> {
+        match chosen.parse::<usize>() {
+            Ok(n) if (1..=len).contains(&n) => Ok(n - 1),
+            _ => Err(anyhow!("Invalid choice `{chosen}`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(Picker::parse_choice("1", 3).unwrap(), 0);
+        assert_eq!(Picker::parse_choice("3", 3).unwrap(), 2);
+        assert!(Picker::parse_choice("0", 3).is_err());
+        assert!(Picker::parse_choice("4", 3).is_err());
+        assert!(Picker::parse_choice("a", 3).is_err());
+        assert!(Picker::parse_choice("", 3).is_err());
+        assert_eq!(Picker::parse_answer("", 3).unwrap(), 0);
+        assert_eq!(Picker::parse_answer("2", 3).unwrap(), 1);
+        assert!(Picker::parse_answer("4", 3).is_err());
+    }
+
+    #[test]
+    fn test_pick_command() {
+        let items = vec!["a".to_string(), "b".to_string()];
+        let command = ["sed".to_string(), "-n".to_string(), "2p".to_string()];
+
+        assert_eq!(Picker::pick(&items, Some(&command)).unwrap(), 1);
+    }
+}